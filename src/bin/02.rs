@@ -1,51 +1,121 @@
+use std::ops::RangeInclusive;
+
+use advent_of_code::{render::visualize_requested, template::option};
+use itertools::Itertools;
+
 advent_of_code::solution!(2);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let (safe_reports, _) = split_reports_by_safety(input);
-    Some(safe_reports.len() as u32)
+/// Decides whether a report is safe: its levels all increase or all decrease, by a step within
+/// `steps`, after removing at most `tolerance` levels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Checker {
+    steps: RangeInclusive<u32>,
+    tolerance: usize,
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let (safe_reports, unsafe_reports) = split_reports_by_safety(input);
-    // For each unsafe report, find how many can be made safe by removing one value
-    let mut count = safe_reports.len();
-    for report in unsafe_reports {
-        for i in 0..report.len() {
-            let mut new_report = report.clone();
-            new_report.remove(i);
-            if is_safe(&new_report) {
-                count += 1;
-                break;
+impl Checker {
+    /// The puzzle's checker: steps of 1 to 3, or the `--min-step` and `--max-step` options.
+    fn from_args(tolerance: usize) -> Self {
+        let steps = option("--min-step").unwrap_or(1)..=option("--max-step").unwrap_or(3);
+        Self { steps, tolerance }
+    }
+
+    fn step_ok(&self, from: u32, to: u32, increasing: bool) -> bool {
+        (to > from) == increasing && self.steps.contains(&from.abs_diff(to))
+    }
+
+    /// The fewest indices of levels to remove to make `report` safe, or [`None`] if that takes
+    /// more than `tolerance` removals. Takes O(n * tolerance) time.
+    fn check(&self, report: &[u32]) -> Option<Vec<usize>> {
+        let increasing = self.check_direction(report, true);
+        let decreasing = self.check_direction(report, false);
+        [increasing, decreasing]
+            .into_iter()
+            .flatten()
+            .min_by_key(Vec::len)
+    }
+
+    fn check_direction(&self, report: &[u32], increasing: bool) -> Option<Vec<usize>> {
+        let (n, k) = (report.len(), self.tolerance);
+        // For each level kept as the last one so far: the fewest removals before it, and the
+        // level kept before it. Only the last `k + 1` levels can come right before it.
+        let mut best: Vec<Option<(usize, Option<usize>)>> = vec![None; n];
+        for i in 0..n {
+            if i <= k {
+                best[i] = Some((i, None));
             }
+            for j in i.saturating_sub(k + 1)..i {
+                let Some((removed, _)) = best[j] else {
+                    continue;
+                };
+                let removed = removed + i - j - 1;
+                if removed <= k
+                    && self.step_ok(report[j], report[i], increasing)
+                    && best[i].is_none_or(|(fewest, _)| removed < fewest)
+                {
+                    best[i] = Some((removed, Some(j)));
+                }
+            }
+        }
+        if n == 0 {
+            return Some(vec![]);
+        }
+        let (mut last, _) = (0..n)
+            .filter_map(|i| best[i].map(|(removed, _)| (i, removed + n - 1 - i)))
+            .filter(|&(_, removed)| removed <= k)
+            .min_by_key(|&(_, removed)| removed)?;
+        let mut kept = vec![false; n];
+        kept[last] = true;
+        while let Some((_, Some(previous))) = best[last] {
+            kept[previous] = true;
+            last = previous;
         }
+        Some((0..n).filter(|&i| !kept[i]).collect())
     }
-    Some(count as u32)
 }
 
-fn is_safe(report: &Vec<u32>) -> bool {
-    (report.windows(2).all(|w| w[0] < w[1]) || report.windows(2).all(|w| w[0] > w[1]))
-        && report.windows(2).all(|w| w[0].abs_diff(w[1]) <= 3)
+fn parse(input: &str) -> Vec<Vec<u32>> {
+    input
+        .lines()
+        .map(|line| {
+            line.split_ascii_whitespace()
+                .map(|s| s.parse().unwrap())
+                .collect()
+        })
+        .collect()
 }
 
-fn split_reports_by_safety(input: &str) -> (Vec<Vec<u32>>, Vec<Vec<u32>>) {
-    // Parse the input into a vector of lines
-    let reports = input.lines().collect::<Vec<&str>>();
-    let mut safe_reports = vec![];
-    let mut unsafe_reports = vec![];
-    // Over each line, split by whitespace, and parse the elements as u32
-    for line in reports {
-        let report = line
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
-        // If all values are decreasing or increasing, and each adjacent pair has an absolute difference less than or equal to 3, add to safe_reports
-        if is_safe(&report) {
-            safe_reports.push(report);
-        } else {
-            unsafe_reports.push(report);
+/// Counts the safe reports, printing which levels had to go under `--visualize`.
+fn count_safe(input: &str, checker: &Checker) -> usize {
+    let reports = parse(input);
+    let results = reports.iter().map(|report| checker.check(report));
+    if visualize_requested() {
+        for (report, result) in reports.iter().zip(results.clone()) {
+            let levels = report.iter().join(" ");
+            match result {
+                None => println!("{levels}: unsafe"),
+                Some(removed) if removed.is_empty() => println!("{levels}: safe"),
+                Some(removed) => {
+                    let removed = removed
+                        .iter()
+                        .map(|&i| format!("level {} ({})", i + 1, report[i]))
+                        .join(", ");
+                    println!("{levels}: safe without {removed}");
+                }
+            }
         }
     }
-    (safe_reports, unsafe_reports)
+    results.flatten().count()
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    Some(count_safe(input, &Checker::from_args(0)) as u32)
+}
+
+/// Tolerates removing one level, or `--tolerance <k>` levels.
+pub fn part_two(input: &str) -> Option<u32> {
+    let tolerance = option("--tolerance").unwrap_or(1);
+    Some(count_safe(input, &Checker::from_args(tolerance)) as u32)
 }
 
 #[cfg(test)]
@@ -67,4 +137,28 @@ mod tests {
         // The result of part one should be less than or equal to the result of part two
         assert!(result_one.unwrap() <= result_two.unwrap());
     }
+
+    #[test]
+    fn test_removes_up_to_k_levels() {
+        let checker = |tolerance| Checker {
+            steps: 1..=3,
+            tolerance,
+        };
+        let input = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9";
+        assert_eq!(count_safe(input, &checker(0)), 2);
+        assert_eq!(count_safe(input, &checker(1)), 4);
+
+        assert_eq!(checker(1).check(&[1, 2, 7, 3, 4]), Some(vec![2]));
+        assert_eq!(checker(1).check(&[9, 1, 2, 3]), Some(vec![0]));
+        assert_eq!(checker(1).check(&[1, 5, 9, 2, 3]), None);
+        assert_eq!(checker(2).check(&[1, 5, 9, 2, 3]), Some(vec![1, 2]));
+        assert_eq!(checker(2).check(&[1, 2, 3, 40, 50]), Some(vec![3, 4]));
+
+        let wide = Checker {
+            steps: 2..=10,
+            tolerance: 0,
+        };
+        assert_eq!(wide.check(&[1, 3, 13]), Some(vec![]));
+        assert_eq!(wide.check(&[1, 2]), None);
+    }
 }
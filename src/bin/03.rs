@@ -1,84 +1,105 @@
 advent_of_code::solution!(3);
 
-use miette::miette;
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::{anychar, u32},
-    combinator::value,
-    multi::{many1, many_till},
-    sequence::{delimited, separated_pair},
-    IResult, Parser,
+use std::ops::Range;
+
+use advent_of_code::{
+    render::{explain_requested, Color, GridRenderer},
+    vm::{Effect, InstructionSet, Outcome, Pattern, Trace, Vm},
 };
 
-#[derive(Debug, Clone, Copy)]
-enum Instruction {
-    Mul(u32, u32),
-    // Add(u32, u32),
-    Do,
-    Dont,
-}
-#[derive(PartialEq, Eq)]
-enum ShouldProcess {
-    Do,
-    Dont,
-}
+const SUM: &str = "sum";
+const ENABLED: &str = "enabled";
 
-fn parse_mul_instruction(input: &str) -> IResult<&str, Instruction> {
-    let (input, _) = tag("mul")(input)?;
-    let (input, pair) = delimited(tag("("), separated_pair(u32, tag(","), u32), tag(")"))(input)?;
-    Ok((input, Instruction::Mul(pair.0, pair.1)))
+/// The `mul` instructions, which only honour `do()` and `don't()` if `conditionals` is set.
+fn instruction_set(conditionals: bool) -> InstructionSet {
+    let mul = Pattern::new("mul", 2, Effect::AddProduct(SUM.to_string()));
+    if !conditionals {
+        return InstructionSet::new().max_digits(3).instruction(mul);
+    }
+    InstructionSet::new()
+        .max_digits(3)
+        .instruction(mul.guard(ENABLED))
+        .instruction(Pattern::new(
+            "do",
+            0,
+            Effect::Flag(ENABLED.to_string(), true),
+        ))
+        .instruction(Pattern::new(
+            "don't",
+            0,
+            Effect::Flag(ENABLED.to_string(), false),
+        ))
 }
 
-fn parser_rules(input: &str) -> IResult<&str, Instruction> {
-    alt((
-        value(Instruction::Dont, tag("don't()")),
-        value(Instruction::Do, tag("do()")),
-        parse_mul_instruction,
-    ))(input)
+/// The `(row, col)` of every character in `span` of `input`, for a [`GridRenderer`].
+fn positions(input: &str, span: Range<usize>) -> Vec<(usize, usize)> {
+    let mut positions = vec![];
+    let mut line_start = 0;
+    for (row, line) in input.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\r', '\n']);
+        for (col, (i, _)) in text.char_indices().enumerate() {
+            if span.contains(&(line_start + i)) {
+                positions.push((row, col));
+            }
+        }
+        line_start += line.len();
+    }
+    positions
 }
 
-fn parse(input: &str) -> IResult<&str, Vec<Instruction>> {
-    many1(many_till(anychar, parser_rules).map(|(_discard, instructions)| instructions))(input)
-}
+/// The corrupted memory with executed `mul`s in green, skipped ones in red and toggles
+/// highlighted, followed by what every instruction added to the sum.
+fn explain(input: &str, trace: &Trace) -> String {
+    let mut view = GridRenderer::from_lines(input);
+    for step in &trace.steps {
+        let positions = positions(input, step.instruction.span.clone());
+        match step.outcome {
+            Outcome::Register { .. } => view.color(positions, Color::Green),
+            Outcome::Skipped { .. } | Outcome::Overflowed { .. } => {
+                view.color(positions, Color::Red)
+            }
+            Outcome::Flag { .. } => view.highlight(positions, Color::Yellow),
+        };
+    }
+    view.dim_unstyled();
 
-fn execute(instructions: &[Instruction]) -> Option<u32> {
-    let (_, sum) = instructions.iter().fold(
-        (ShouldProcess::Do, 0),
-        |(should_process, sum), instruction| match instruction {
-            Instruction::Mul(a, b) => {
-                if should_process == ShouldProcess::Do {
-                    (should_process, sum + a * b)
-                } else {
-                    (should_process, sum)
-                }
+    let mut sum = 0;
+    let mut table = String::new();
+    for step in &trace.steps {
+        let contribution = match step.outcome {
+            Outcome::Register { value, .. } => {
+                format!("{:+}", value - std::mem::replace(&mut sum, value))
             }
-            // Instruction::Add(a, b) => {
-            //     if should_process == ShouldProcess::Do {
-            //         (should_process, sum + a + b)
-            //     } else {
-            //         (should_process, sum)
-            //     }
-            // }
-            Instruction::Do => (ShouldProcess::Do, sum),
-            Instruction::Dont => (ShouldProcess::Dont, sum),
-        },
-    );
-    Some(sum)
+            _ => String::new(),
+        };
+        let line = format!("{:<60}{contribution:>8}", step.to_string());
+        table += line.trim_end();
+        table.push('\n');
+    }
+    format!("{view}\n{table}")
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let (_, instructions) = parse(input)
-        .map_err(|e| miette!("parse failed! {}", e))
-        .ok()?;
-    execute(&instructions)
+fn execute(input: &str, conditionals: bool) -> Option<u64> {
+    let set = instruction_set(conditionals);
+    let mut vm = Vm::new(&set);
+    let trace = vm.run(input);
+    if explain_requested() {
+        print!("{}", explain(input, &trace));
+    }
+    if let Some(step) = trace.overflow() {
+        eprintln!("The sum overflowed: {step}");
+        return None;
+    }
+    let sum = vm.register(SUM);
+    Some(u64::try_from(sum).expect("products of unsigned arguments add up to a positive sum"))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let (_, instructions) = parse(input)
-        .map_err(|e| miette!("parse failed! {}", e))
-        .ok()?;
-    execute(&instructions)
+pub fn part_one(input: &str) -> Option<u64> {
+    execute(input, false)
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    execute(input, true)
 }
 
 #[cfg(test)]
@@ -91,6 +112,12 @@ mod tests {
             "examples", DAY, 1,
         ));
         assert_eq!(example, Some(161));
+
+        // Part one ignores do() and don't().
+        let toggled = part_one(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(toggled, Some(161));
     }
 
     #[test]
@@ -100,4 +127,18 @@ mod tests {
         ));
         assert_eq!(result, Some(48));
     }
+
+    #[test]
+    fn test_explain() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let set = instruction_set(true);
+        let trace = Vm::new(&set).run(input);
+        let explanation = explain(input, &trace);
+        let table: Vec<&str> = explanation.lines().skip(2).map(str::trim_end).collect();
+        assert_eq!(table.len(), 6);
+        assert!(table[0].starts_with("     1  mul(2,4)") && table[0].ends_with("+8"));
+        assert!(table[2].contains("skipped: enabled is off since offset 20"));
+        assert!(table[5].ends_with("+40"));
+        assert_eq!(positions("ab\ncd", 1..4), [(0, 1), (1, 0)]);
+    }
 }
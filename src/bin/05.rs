@@ -1,55 +1,93 @@
-#![feature(num_midpoint)]
-use nom::multi::separated_list1;
+use advent_of_code::{
+    graph::{topological_sort_subset, CycleError},
+    num::midpoint,
+    parse::{comma_list, lines, number, separated},
+    render::visualize_requested,
+};
 use nom::{
-    character::complete::{char, digit1, line_ending},
-    combinator::map_res,
+    character::complete::line_ending,
     multi::fold_many1,
     sequence::{separated_pair, terminated},
     IResult,
 };
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
 advent_of_code::solution!(5);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    if let Ok((_input, (rules, updates))) =
-        separated_pair(parse_rules, line_ending, parse_updates)(input)
-    {
-        let middle_page_sum = updates
+/// A broken rule: `before` must come before `after`, but the update has them the other way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Violation {
+    before: u32,
+    after: u32,
+}
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rule {}|{} is broken: {} comes first",
+            self.before, self.after, self.after
+        )
+    }
+}
+
+/// The page ordering rules, as both an adjacency map and a set of `(before, after)` pairs.
+struct Rules {
+    afters: HashMap<u32, Vec<u32>>,
+    pairs: HashSet<(u32, u32)>,
+}
+impl Rules {
+    fn new(afters: HashMap<u32, Vec<u32>>) -> Self {
+        let pairs = afters
             .iter()
-            .map(|update| {
-                let is_valid = update.iter().enumerate().all(|(i, &page)| {
-                    // If there's no rule for this page, it's valid
-                    match rules.get(&page) {
-                        None => true,
-                        Some(afters) => {
-                            // Check if all subsequent pages follow the rule
-                            update[i + 1..]
-                                .iter()
-                                .all(|next_page| afters.contains(next_page))
-                                && !update[..i].iter().any(|p| afters.contains(p))
-                        }
-                    }
-                });
+            .flat_map(|(&page, afters)| afters.iter().map(move |&after| (page, after)))
+            .collect();
+        Self { afters, pairs }
+    }
 
-                if is_valid {
-                    #[cfg(debug_assertions)]
-                    dbg!(&update);
-                    update[0usize.midpoint(update.len())]
-                } else {
-                    0
-                }
+    /// Every rule the update breaks.
+    fn violations(&self, update: &[u32]) -> Vec<Violation> {
+        update
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &after)| {
+                update[i + 1..]
+                    .iter()
+                    .filter(move |&&before| self.pairs.contains(&(before, after)))
+                    .map(move |&before| Violation { before, after })
             })
-            .sum();
-        Some(middle_page_sum)
-    } else {
-        None
+            .collect()
+    }
+
+    /// Sorts the update with the rules that apply to its pages. The full rule set may be cyclic and
+    /// leaves some pages unordered, so this is a topological sort rather than a comparator sort,
+    /// and it reports rules that contradict each other within the update.
+    fn sort(&self, update: &[u32]) -> Result<Vec<u32>, CycleError<u32>> {
+        topological_sort_subset(&self.afters, update)
     }
 }
 
+fn parse(input: &str) -> Option<(Rules, Vec<Vec<u32>>)> {
+    let (_, (rules, updates)) =
+        separated_pair(parse_rules, line_ending, parse_updates)(input).ok()?;
+    Some((Rules::new(rules), updates))
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let (rules, updates) = parse(input)?;
+    let middle_page_sum = updates
+        .iter()
+        .filter(|update| rules.violations(update).is_empty())
+        .map(|update| update[midpoint(0, update.len())])
+        .sum();
+    Some(middle_page_sum)
+}
+
 fn parse_rules(input: &str) -> IResult<&str, HashMap<u32, Vec<u32>>> {
     fold_many1(
-        terminated(parse_rule, line_ending),
+        terminated(separated(number, '|', number), line_ending),
         HashMap::default,
         |mut map, (page, after)| {
             map.entry(page)
@@ -60,138 +98,32 @@ fn parse_rules(input: &str) -> IResult<&str, HashMap<u32, Vec<u32>>> {
     )(input)
 }
 fn parse_updates(input: &str) -> IResult<&str, Vec<Vec<u32>>> {
-    separated_list1(line_ending, parse_update)(input)
-}
-fn parse_update(input: &str) -> IResult<&str, Vec<u32>> {
-    separated_list1(char(','), parse_u32)(input)
-}
-fn parse_rule(input: &str) -> IResult<&str, (u32, u32)> {
-    separated_pair(parse_u32, char('|'), parse_u32)(input)
-}
-fn parse_u32(input: &str) -> IResult<&str, u32> {
-    map_res(digit1, str::parse)(input)
+    lines(comma_list)(input)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    if let Ok((_input, (rules, updates))) =
-        separated_pair(parse_rules, line_ending, parse_updates)(input)
-    {
-        let middle_page_sum = updates
-            .iter()
-            .inspect(|&update| {
-                #[cfg(debug_assertions)]
-                dbg!("Checking update:", update);
-            })
-            .filter(|update| {
-                let invalid = !is_valid_order(update, &rules);
-                #[cfg(debug_assertions)]
-                if invalid {
-                    dbg!("Invalid update:", update);
-                }
-                invalid
-            })
-            .map(|update| {
-                let mut pages = update.clone();
-                let mut attempts = 0;
-                const MAX_ATTEMPTS: usize = 1000;
-
-                while !is_valid_order(&pages, &rules) {
-                    attempts += 1;
-                    if attempts > MAX_ATTEMPTS {
-                        #[cfg(debug_assertions)]
-                        println!("Failed to find valid order for: {:?}", update);
-                        return 0;
-                    }
-
-                    let mut made_swap = false;
-
-                    // Forward pass - check each position with the next
-                    for i in 0..pages.len() - 1 {
-                        if should_swap(&pages, i, i + 1, &rules) {
-                            pages.swap(i, i + 1);
-                            made_swap = true;
-                            break;
-                        }
-                    }
-
-                    // If no forward swaps worked, try backward pass
-                    if !made_swap {
-                        for i in (1..pages.len()).rev() {
-                            if should_swap(&pages, i - 1, i, &rules) {
-                                pages.swap(i - 1, i);
-                                made_swap = true;
-                                break;
-                            }
-                        }
-                    }
-
-                    if !made_swap {
-                        #[cfg(debug_assertions)]
-                        println!("Stuck - no valid swaps possible for: {:?}", update);
-                        return 0;
-                    }
-                }
-
-                #[cfg(debug_assertions)]
-                dbg!("Corrected to:", &pages);
-
-                pages[0usize.midpoint(pages.len())]
-            })
-            .sum();
-        Some(middle_page_sum)
-    } else {
-        None
-    }
-}
-
-// Helper function to determine if two adjacent positions should be swapped
-fn should_swap(pages: &[u32], i: usize, j: usize, rules: &HashMap<u32, Vec<u32>>) -> bool {
-    let page_i = pages[i];
-    let page_j = pages[j];
-
-    // Check if i must come after j (meaning we should swap)
-    if let Some(rules_j) = rules.get(&page_j) {
-        if rules_j.contains(&page_i) {
-            return true;
+    let (rules, updates) = parse(input)?;
+    let mut middle_page_sum = 0;
+    for update in &updates {
+        let violations = rules.violations(update);
+        if violations.is_empty() {
+            continue;
         }
-    }
-
-    // Check if j must come after i (meaning we shouldn't swap)
-    if let Some(rules_i) = rules.get(&page_i) {
-        if rules_i.contains(&page_j) {
-            return false;
-        }
-    }
-
-    // If no direct rules, check transitive relationships
-    // Look at all rules that mention either i or j
-    for (page, must_come_after) in rules {
-        if must_come_after.contains(&page_i) && *page == page_j {
-            return false; // j must come before i
+        if visualize_requested() {
+            println!("{update:?}");
+            for violation in &violations {
+                println!("  {violation}");
+            }
         }
-        if must_come_after.contains(&page_j) && *page == page_i {
-            return true; // i must come before j
+        match rules.sort(update) {
+            Ok(pages) => middle_page_sum += pages[midpoint(0, pages.len())],
+            Err(e) => {
+                eprintln!("Cannot reorder {update:?}: {e}");
+                return None;
+            }
         }
     }
-
-    // If no rules dictate order, maintain current position
-    false
-}
-
-// Add this helper function
-fn is_valid_order(update: &[u32], rules: &HashMap<u32, Vec<u32>>) -> bool {
-    update
-        .iter()
-        .enumerate()
-        .all(|(i, &page)| match rules.get(&page) {
-            None => true,
-            Some(afters) => {
-                update[i + 1..]
-                    .iter()
-                    .all(|next_page| afters.contains(next_page))
-                    && !update[..i].iter().any(|p| afters.contains(p))
-            }
-        })
+    Some(middle_page_sum)
 }
 
 #[cfg(test)]
@@ -213,6 +145,20 @@ mod tests {
         assert_eq!(updates, vec![vec![12, 45], vec![45, 78], vec![12, 34]]);
     }
 
+    #[test]
+    fn test_explains_and_rejects_orderings() {
+        let rules = Rules::new(HashMap::from([(1, vec![2, 3]), (2, vec![3]), (3, vec![1])]));
+        assert_eq!(
+            rules.violations(&[2, 1]),
+            vec![Violation {
+                before: 1,
+                after: 2
+            }]
+        );
+        assert_eq!(rules.sort(&[3, 2]), Ok(vec![2, 3]));
+        assert!(rules.sort(&[1, 2, 3]).is_err());
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -222,7 +168,6 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        dbg!(&result);
         assert_eq!(result, Some(123));
     }
 }
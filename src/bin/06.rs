@@ -1,3 +1,11 @@
+use advent_of_code::{
+    bitset::GridSet,
+    direction::Dir4,
+    image::{render_path, render_requested, Image, ImageSequence},
+    recorder::Recorder,
+    render::{visualize_requested, Color, GridRenderer},
+};
+use rayon::prelude::*;
 use std::{collections::HashSet, ops::Add};
 
 advent_of_code::solution!(6);
@@ -17,60 +25,35 @@ impl Add<Coord> for Coord {
         }
     }
 }
-#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-impl Direction {
-    fn turn(&mut self) {
-        match self {
-            Direction::North => *self = Direction::East,
-            Direction::East => *self = Direction::South,
-            Direction::South => *self = Direction::West,
-            Direction::West => *self = Direction::North,
-        }
-    }
-    fn next_step(&self) -> Coord {
-        match self {
-            Direction::North => Coord { x: 0, y: -1 },
-            Direction::East => Coord { x: 1, y: 0 },
-            Direction::South => Coord { x: 0, y: 1 },
-            Direction::West => Coord { x: -1, y: 0 },
-        }
+impl From<Dir4> for Coord {
+    fn from(direction: Dir4) -> Coord {
+        let (x, y) = direction.offset();
+        Coord { x, y }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Location {
     coord: Coord,
-    direction: Direction,
+    direction: Dir4,
 }
+/// A location inside the grid as a `((row, col), direction)` key for a [`GridSet`].
+type Key = ((usize, usize), Dir4);
 impl Location {
+    fn key(&self) -> Key {
+        (
+            (self.coord.y as usize, self.coord.x as usize),
+            self.direction,
+        )
+    }
     fn next_step(&self) -> Location {
-        match self.direction {
-            Direction::North => Location {
-                coord: self.coord + Direction::North.next_step(),
-                direction: self.direction,
-            },
-            Direction::East => Location {
-                coord: self.coord + Direction::East.next_step(),
-                direction: self.direction,
-            },
-            Direction::South => Location {
-                coord: self.coord + Direction::South.next_step(),
-                direction: self.direction,
-            },
-            Direction::West => Location {
-                coord: self.coord + Direction::West.next_step(),
-                direction: self.direction,
-            },
+        Location {
+            coord: self.coord + self.direction.into(),
+            direction: self.direction,
         }
     }
     fn turn(&mut self) -> &mut Self {
-        self.direction.turn();
+        self.direction = self.direction.turn_cw();
         self
     }
 }
@@ -79,32 +62,23 @@ struct State {
     grid: Grid,
     current_location: Location,
     next_location: Location,
-    distinct_traveled_locations: HashSet<Location>,
-    distinct_obstacles: HashSet<Location>,
+    distinct_traveled_locations: GridSet<Key>,
+    distinct_obstacles: GridSet<Key>,
 }
 impl State {
     fn new(grid: Grid, start_location: Location) -> Self {
+        let (rows, cols) = (grid.len(), grid[0].len());
+        let mut distinct_traveled_locations = GridSet::new(rows, cols);
+        distinct_traveled_locations.insert(start_location.key());
         Self {
             grid,
-            distinct_traveled_locations: HashSet::from([Location {
-                coord: start_location.coord,
-                direction: Direction::North,
-            }]),
-            distinct_obstacles: HashSet::new(),
+            distinct_traveled_locations,
+            distinct_obstacles: GridSet::new(rows, cols),
             next_location: start_location,
             current_location: start_location,
         }
     }
 
-    fn reset(&mut self) {
-        let start = find_start(&self.grid).unwrap();
-        self.distinct_traveled_locations.clear();
-        self.distinct_obstacles.clear();
-        self.current_location = Location {
-            coord: start,
-            direction: Direction::North,
-        };
-    }
     fn is_next_cell_beyond_grid(&mut self) -> bool {
         self.next_location = self.current_location.next_step();
 
@@ -114,9 +88,11 @@ impl State {
             || self.next_location.coord.y >= self.grid.len() as isize
     }
 
-    fn traverse(&mut self) -> bool {
+    /// Walks until the guard leaves the grid or loops, calling `on_step` after every step.
+    fn traverse_with(&mut self, mut on_step: impl FnMut(&Self)) -> bool {
         while !self.is_next_cell_beyond_grid() {
             let looped = self.try_step();
+            on_step(self);
             if looped {
                 return true;
             }
@@ -128,7 +104,7 @@ impl State {
         self.grid[self.next_location.coord.y as usize][self.next_location.coord.x as usize] == '#'
     }
     fn is_loop(&mut self) -> bool {
-        self.distinct_obstacles.contains(&self.next_location)
+        self.distinct_obstacles.contains(&self.next_location.key())
     }
     fn try_step(&mut self) -> bool {
         //Check if the next coord is an obstacle
@@ -136,23 +112,16 @@ impl State {
             if self.is_loop() {
                 return true;
             } else {
-                self.distinct_obstacles.insert(self.next_location);
+                self.distinct_obstacles.insert(self.next_location.key());
             }
             self.current_location.turn();
         } else {
             self.current_location = self.next_location;
-            self.distinct_traveled_locations.insert(self.next_location);
+            self.distinct_traveled_locations
+                .insert(self.next_location.key());
         }
         false
     }
-    fn add_obstacle(&mut self, location: &Location) -> char {
-        let previous = self.grid[location.coord.y as usize][location.coord.x as usize];
-        self.grid[location.coord.y as usize][location.coord.x as usize] = '#';
-        previous
-    }
-    fn remove_obstacle(&mut self, location: &Location, previous: char) {
-        self.grid[location.coord.y as usize][location.coord.x as usize] = previous;
-    }
 }
 
 fn make_grid(input: &str) -> Grid {
@@ -172,59 +141,177 @@ fn find_start(grid: &Vec<Vec<char>>) -> Option<Coord> {
     None
 }
 
+fn view(state: &State) -> GridRenderer {
+    let (rows, cols) = (state.grid.len(), state.grid[0].len());
+    let mut view = GridRenderer::new(rows, cols, |(row, col)| state.grid[row][col]);
+    let obstacles = (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .filter(|&(row, col)| state.grid[row][col] == '#');
+    let path = state.distinct_traveled_locations.iter();
+    let guard = state.current_location;
+    view.color(obstacles, Color::Red)
+        .path(path, Color::Green)
+        .mark(
+            [(guard.coord.y as usize, guard.coord.x as usize)],
+            guard.direction.arrow(),
+            Color::Yellow,
+        )
+        .dim_unstyled();
+    view
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     let grid = make_grid(input);
     let current_location: Location = Location {
         coord: find_start(&grid).unwrap(),
-        direction: Direction::North,
+        direction: Dir4::North,
     };
     let mut state = State::new(grid, current_location);
-    state.traverse();
+    let every = state.grid.len() * state.grid[0].len() / 500;
+    let mut recorder = Recorder::from_args().every(every);
+    let mut frames = ImageSequence::from_args(DAY, 1).every(every);
+    state.traverse_with(|state| {
+        recorder.capture_with(|| view(state));
+        if let Err(e) = frames.capture_with(|| Image::from_renderer(&view(state), 4)) {
+            eprintln!("Failed to render frame: {e}");
+        }
+    });
+    recorder.capture_last_with(|| view(&state));
+    recorder.finish(DAY, 1);
+    if visualize_requested() {
+        println!("{}", view(&state));
+    }
+    if render_requested() {
+        let path = render_path(DAY, 1);
+        match Image::from_renderer(&view(&state), 4).save(&path) {
+            Ok(()) => println!(
+                "Rendered {} frames and the final path to \"{}\"",
+                frames.len(),
+                path.display()
+            ),
+            Err(e) => eprintln!("Failed to render path: {e}"),
+        }
+    }
     // Filter to a unique set of *coordinates* in the distinct_locations (irrespective of direction facing while traversing)
-    let unique_locations: HashSet<Coord> = state
+    let unique_locations: HashSet<(usize, usize)> = state
         .distinct_traveled_locations
         .iter()
-        .map(|l| l.coord)
+        .map(|(position, _)| position)
         .collect();
     Some(unique_locations.len() as u32)
 }
 
+/// For every cell and direction, the last cell the guard reaches walking that way before an
+/// obstacle, or [`None`] if it walks off the grid.
+struct JumpTable {
+    rows: usize,
+    cols: usize,
+    stops: Vec<[Option<(usize, usize)>; 4]>,
+}
+impl JumpTable {
+    fn new(grid: &Grid) -> Self {
+        let (rows, cols) = (grid.len(), grid[0].len());
+        let mut stops = vec![[None; 4]; rows * cols];
+        for direction in Dir4::iter() {
+            // Fill cells in an order where the cell ahead of each one is already filled.
+            let forward = matches!(direction, Dir4::North | Dir4::West);
+            for i in 0..rows * cols {
+                let index = if forward { i } else { rows * cols - 1 - i };
+                let cell = (index / cols, index % cols);
+                stops[index][direction as usize] = match direction.step(cell, rows, cols) {
+                    None => None,
+                    Some((row, col)) if grid[row][col] == '#' => Some(cell),
+                    Some((row, col)) => stops[row * cols + col][direction as usize],
+                };
+            }
+        }
+        Self { rows, cols, stops }
+    }
+
+    /// Whether the guard loops when starting at `start` with an extra obstacle at `obstacle`.
+    /// `turns` is scratch space, cleared before use.
+    fn loops_with(&self, start: Key, obstacle: (usize, usize), turns: &mut GridSet<Key>) -> bool {
+        turns.clear();
+        let (mut position, mut direction) = start;
+        loop {
+            let stop = self.stops[position.0 * self.cols + position.1][direction as usize];
+            position = match stop {
+                _ if Self::blocks(position, direction, stop, obstacle) => direction
+                    .opposite()
+                    .step(obstacle, self.rows, self.cols)
+                    .unwrap(),
+                Some(stop) => stop,
+                None => return false,
+            };
+            direction = direction.turn_cw();
+            if !turns.insert((position, direction)) {
+                return true;
+            }
+        }
+    }
+
+    /// Whether `obstacle` is on the way from `position` to `stop` (or to the edge of the grid).
+    fn blocks(
+        position: (usize, usize),
+        direction: Dir4,
+        stop: Option<(usize, usize)>,
+        obstacle: (usize, usize),
+    ) -> bool {
+        let ahead = |(row, col): (usize, usize)| match direction {
+            Dir4::North => col == position.1 && row < position.0,
+            Dir4::South => col == position.1 && row > position.0,
+            Dir4::West => row == position.0 && col < position.1,
+            Dir4::East => row == position.0 && col > position.1,
+        };
+        let before_stop = |(row, col): (usize, usize)| match direction {
+            Dir4::North => row >= stop.map_or(0, |stop| stop.0),
+            Dir4::South => row <= stop.map_or(usize::MAX, |stop| stop.0),
+            Dir4::West => col >= stop.map_or(0, |stop| stop.1),
+            Dir4::East => col <= stop.map_or(usize::MAX, |stop| stop.1),
+        };
+        ahead(obstacle) && before_stop(obstacle)
+    }
+}
+
 pub fn part_two(input: &str) -> Option<u32> {
     let grid = make_grid(input);
-    let current_location: Location = Location {
+    let start = Location {
         coord: find_start(&grid).unwrap(),
-        direction: Direction::North,
+        direction: Dir4::North,
     };
-    let mut state = State::new(grid, current_location);
-    state.traverse();
-    // Make a copy of the distinct positions
-    let mut locations: Vec<Location> = state.distinct_traveled_locations.iter().cloned().collect();
-    // Find the index of the original guard position
-    let guard_position = locations
-        .iter()
-        .position(|p| p == &current_location)
-        .unwrap();
-    // Remove the original guard position
-    locations.remove(guard_position);
-    // Sort the positions
-    locations.sort_by(|a, b| a.coord.x.cmp(&b.coord.x).then(a.coord.y.cmp(&b.coord.y)));
-
-    let mut looped_locations: HashSet<Coord> = HashSet::new();
-    for location in &locations {
-        state.reset();
-        let original_char = state.add_obstacle(location);
-        let looped = state.traverse();
-        state.remove_obstacle(location, original_char);
-        if looped {
-            looped_locations.insert(location.coord);
+    let (rows, cols) = (grid.len(), grid[0].len());
+
+    // Walk the original route, noting each cell the first time the guard is about to enter it:
+    // an obstacle there can only change the route from that point on.
+    let mut candidates: Vec<(Key, (usize, usize))> = vec![];
+    let mut entered = GridSet::new(rows, cols);
+    entered.insert(start.key().0);
+    let mut previous = start;
+    let mut state = State::new(grid, start);
+    state.traverse_with(|state| {
+        let current = state.current_location;
+        if current.coord != previous.coord && entered.insert(current.key().0) {
+            candidates.push(((previous.key().0, current.direction), current.key().0));
         }
-    }
-    Some(looped_locations.len() as u32)
+        previous = current;
+    });
+
+    let jumps = JumpTable::new(&state.grid);
+    let looped = candidates
+        .par_iter()
+        .map_init(
+            || GridSet::new(rows, cols),
+            |turns, &(from, obstacle)| jumps.loops_with(from, obstacle, turns),
+        )
+        .filter(|&looped| looped)
+        .count();
+    Some(looped as u32)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::crosscheck::CrossCheck;
 
     #[test]
     fn test_part_one() {
@@ -237,4 +324,29 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    /// Tries every free cell by walking the full route with an obstacle there.
+    fn brute_force(input: &str) -> Option<u32> {
+        let grid = make_grid(input);
+        let start = Location {
+            coord: find_start(&grid)?,
+            direction: Dir4::North,
+        };
+        let mut looped = 0;
+        for (row, col) in
+            (0..grid.len()).flat_map(|row| (0..grid[row].len()).map(move |col| (row, col)))
+        {
+            if grid[row][col] == '.' {
+                let mut blocked = grid.clone();
+                blocked[row][col] = '#';
+                looped += State::new(blocked, start).traverse_with(|_| {}) as u32;
+            }
+        }
+        Some(looped)
+    }
+
+    #[test]
+    fn test_part_two_matches_brute_force() {
+        CrossCheck::new(DAY).sizes(4..12).run(part_two, brute_force);
+    }
 }
@@ -1,15 +1,9 @@
-use itertools::Itertools;
-use nom::{
-    bytes::complete::tag,
-    character::{
-        self,
-        complete::{line_ending, space1},
-    },
-    combinator::map,
-    multi::separated_list1,
-    sequence::separated_pair,
-    IResult,
+use advent_of_code::{
+    equation::{expression, Op, Solver},
+    parse::{key_values, lines, number, number_row},
+    render::visualize_requested,
 };
+use nom::{combinator::map, IResult};
 
 advent_of_code::solution!(7);
 
@@ -19,102 +13,47 @@ struct Equation {
     operands: Vec<u64>,
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Operation {
-    Add,
-    Multiply,
-    Concatenate,
-}
-impl Operation {
-    fn all() -> Vec<Operation> {
-        vec![Operation::Add, Operation::Multiply, Operation::Concatenate]
-    }
-    fn apply(&self, a: u64, b: u64) -> u64 {
-        match self {
-            Operation::Add => a + b,
-            Operation::Multiply => a * b,
-            Operation::Concatenate => {
-                let mut a_str = a.to_string();
-                let b_str = b.to_string();
-                a_str.push_str(&b_str);
-                a_str.parse::<u64>().unwrap()
-            }
-        }
-    }
-}
-
-pub fn part_one(input: &str) -> Option<u64> {
+/// Sums the results of the equations that some combination of `operators` can make true.
+fn total_calibration(input: &str, operators: &[Op]) -> Option<u64> {
     let (_, equations) = parse_equations(input).ok()?;
-    let sum: u64 = equations
+    let solver = Solver::new(operators);
+    let sum = equations
         .iter()
         .filter_map(|equation| {
-            // There is one less operator than operands (it fits in between each operand!)
-            let operator_count = equation.operands.len() - 1;
-            (0..operator_count)
-                .map(|_| vec![Operation::Add, Operation::Multiply])
-                .multi_cartesian_product()
-                .any(|ops| {
-                    let mut ops_iter = ops.iter();
-                    let result = equation
-                        .operands
-                        .iter()
-                        .copied()
-                        .reduce(|acc, next_number| ops_iter.next().unwrap().apply(acc, next_number))
-                        .unwrap();
-                    equation.result == result
-                })
-                .then_some(equation.result)
+            let ops = solver.solve(equation.result, &equation.operands)?;
+            if visualize_requested() {
+                println!(
+                    "{} = {}",
+                    equation.result,
+                    expression(&equation.operands, &ops)
+                );
+            }
+            Some(equation.result)
         })
         .sum();
     Some(sum)
 }
 
+pub fn part_one(input: &str) -> Option<u64> {
+    total_calibration(input, &Op::ADD_MUL)
+}
+
 pub fn part_two(input: &str) -> Option<u64> {
-    let (_, equations) = parse_equations(input).ok()?;
-    let sum: u64 = equations
-        .iter()
-        .filter_map(|equation| {
-            // There is one less operator than operands (it fits in between each operand!)
-            let operator_count = equation.operands.len() - 1;
-            (0..operator_count)
-                .map(|_| Operation::all())
-                .multi_cartesian_product()
-                .any(|ops| {
-                    let mut ops_iter = ops.iter();
-                    let result = equation
-                        .operands
-                        .iter()
-                        .copied()
-                        .reduce(|acc, next_number| ops_iter.next().unwrap().apply(acc, next_number))
-                        .unwrap();
-                    equation.result == result
-                })
-                .then_some(equation.result)
-        })
-        .sum();
-    Some(sum)
+    total_calibration(input, &Op::ALL)
 }
 
 fn parse_equations(input: &str) -> IResult<&str, Vec<Equation>> {
-    separated_list1(
-        line_ending,
-        map(parse_equation, |(result, operands)| Equation {
-            result,
-            operands,
-        }),
-    )(input)
-}
-fn parse_equation(input: &str) -> IResult<&str, (u64, Vec<u64>)> {
     // Each line has a result, followed by a colon, followed by a list of space-separated operands.
-    separated_pair(character::complete::u64, tag(": "), parse_operands)(input)
-}
-fn parse_operands(input: &str) -> IResult<&str, Vec<u64>> {
-    separated_list1(space1, character::complete::u64)(input)
+    lines(map(key_values(number, number_row), |(result, operands)| {
+        Equation { result, operands }
+    }))(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::{crosscheck::CrossCheck, equation::evaluate};
+    use itertools::Itertools;
 
     #[test]
     fn test_part_one() {
@@ -127,4 +66,29 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(11387));
     }
+
+    /// Evaluates every combination of operators left to right.
+    fn brute_force(input: &str, operators: &[Op]) -> Option<u64> {
+        let (_, equations) = parse_equations(input).ok()?;
+        let solvable = |equation: &Equation| {
+            (1..equation.operands.len())
+                .map(|_| operators.iter().copied())
+                .multi_cartesian_product()
+                .any(|ops| evaluate(&equation.operands, &ops) == Some(equation.result))
+        };
+        Some(
+            equations
+                .iter()
+                .filter(|e| solvable(e))
+                .map(|e| e.result)
+                .sum(),
+        )
+    }
+
+    #[test]
+    fn test_matches_exhaustive_search() {
+        let check = CrossCheck::new(DAY).sizes(1..12);
+        check.run(part_one, |input| brute_force(input, &Op::ADD_MUL));
+        check.run(part_two, |input| brute_force(input, &Op::ALL));
+    }
 }
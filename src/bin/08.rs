@@ -3,6 +3,11 @@ use std::{
     ops::{Add, AddAssign, Neg, Sub},
 };
 
+use advent_of_code::{
+    num::gcd,
+    render::{visualize_requested, Color, GridRenderer},
+    template::{flag, option},
+};
 use itertools::Itertools;
 
 advent_of_code::solution!(8);
@@ -68,31 +73,121 @@ fn get_coords(input: &str) -> CharCoords {
         .collect::<Vec<(Coord, char)>>()
 }
 
-// Needs:
-// A hashmap of all the letters and their coordinates
-// A hashset of the antinode coordinates
-// A way to calculate the distance between two nodes of the same letter (frequency)
-// So, for each letter (frequency) pair, calculate distance to all possible antinodes -> insert into HashSet
+/// Which points on the line through two antennas of the same frequency are antinodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Harmonics {
+    /// Points exactly `n` times as far from one antenna as from the other, 2 in the puzzle.
+    Ratio(isize),
+    /// Every point a whole number of steps away from an antenna, the antennas included.
+    AnyMultiple,
+}
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let nodes_grouped_by_character = shared_setup(input);
-    let x_bounds = 0..input.lines().next().unwrap().chars().count() as isize;
-    let y_bounds = 0..input.lines().count() as isize;
-    let mut antinodes = HashSet::new();
-
-    // Now that the nodes are grouped by letter, we can iterate over each combination of coords for a given "frequnecy" (character)
-    for (_character, coords) in nodes_grouped_by_character {
-        for (a, b) in coords.iter().tuple_combinations() {
-            // Calculate Manhattan distance between the two nodes
-            let delta = *b - *a; // The vectored distance between the two nodes
-            antinodes.insert(*a - delta); // The first antinode coord is the node minus the distance
-            antinodes.insert(*b + delta); // The second antinode coord is the node plus the distance
+/// Finds antinodes on a `width` by `height` map.
+#[derive(Debug, Clone, Copy)]
+struct Antinodes {
+    harmonics: Harmonics,
+    /// Steps by the delta divided by the gcd of its coordinates, so grid points between two
+    /// antennas count too, instead of by the full distance between the antennas.
+    reduce: bool,
+    width: isize,
+    height: isize,
+}
+
+impl Antinodes {
+    /// The puzzle's map size, with `--reduce` turning on [`Antinodes::reduce`].
+    fn new(input: &str, harmonics: Harmonics) -> Self {
+        Self {
+            harmonics,
+            reduce: flag("--reduce"),
+            width: input.lines().next().unwrap().chars().count() as isize,
+            height: input.lines().count() as isize,
         }
     }
-    // Retain the antinodes that are inside the grid
-    antinodes.retain(|coord| x_bounds.contains(&coord.x) && y_bounds.contains(&coord.y));
 
-    Some(antinodes.len() as u32)
+    fn contains(&self, coord: Coord) -> bool {
+        (0..self.width).contains(&coord.x) && (0..self.height).contains(&coord.y)
+    }
+
+    /// The antinodes of one pair of antennas.
+    fn of_pair(&self, a: Coord, b: Coord) -> Vec<Coord> {
+        // Measure everything in steps from `a`, where `b` is `n` steps away.
+        let delta = b - a;
+        let n = if self.reduce {
+            gcd(delta.x, delta.y).max(1)
+        } else {
+            1
+        };
+        let step = Coord {
+            x: delta.x / n,
+            y: delta.y / n,
+        };
+        let at = |t: isize| Coord {
+            x: a.x + step.x * t,
+            y: a.y + step.y * t,
+        };
+        match self.harmonics {
+            Harmonics::Ratio(ratio) => {
+                // Solve |t| = ratio * |t - n| for points `ratio` times as far from `a` as from `b`,
+                // and mirror the solutions for the other way around.
+                let solutions = [(ratio * n, ratio + 1), (ratio * n, ratio - 1)]
+                    .into_iter()
+                    .filter(|&(num, den)| den != 0 && num % den == 0)
+                    .flat_map(|(num, den)| [num / den, n - num / den]);
+                solutions.map(at).filter(|&c| self.contains(c)).collect()
+            }
+            Harmonics::AnyMultiple => {
+                let forward = (0..).map(at).take_while(|&c| self.contains(c));
+                let backward = (1..).map(|t| at(-t)).take_while(|&c| self.contains(c));
+                forward.chain(backward).collect()
+            }
+        }
+    }
+
+    /// Every antinode of every pair of antennas with the same frequency.
+    fn find(&self, antennas: &HashMap<char, Vec<Coord>>) -> HashSet<Coord> {
+        antennas
+            .values()
+            .flat_map(|coords| coords.iter().tuple_combinations())
+            .flat_map(|(&a, &b)| self.of_pair(a, b))
+            .collect()
+    }
+}
+
+/// The map with antennas colored by frequency and antinodes marked.
+fn overlay(
+    input: &str,
+    antennas: &HashMap<char, Vec<Coord>>,
+    antinodes: &HashSet<Coord>,
+) -> GridRenderer {
+    let position = |c: &Coord| (c.y as usize, c.x as usize);
+    let mut view = GridRenderer::from_lines(input);
+    let free = antinodes
+        .iter()
+        .filter(|&&c| view.cell(position(&c)).is_some_and(|cell| cell.ch == '.'))
+        .map(position)
+        .collect_vec();
+    view.highlight(antinodes.iter().map(position), Color::Blue)
+        .mark(free, '#', Color::Red);
+    for (i, frequency) in antennas.keys().sorted().enumerate() {
+        view.color(antennas[frequency].iter().map(position), Color::nth(i));
+    }
+    view.dim_unstyled();
+    view
+}
+
+fn count_antinodes(input: &str, harmonics: Harmonics) -> usize {
+    let antennas = shared_setup(input);
+    let antinodes = Antinodes::new(input, harmonics).find(&antennas);
+    if visualize_requested() {
+        println!("{}", overlay(input, &antennas, &antinodes));
+    }
+    antinodes.len()
+}
+
+/// Antinodes twice as far from one antenna as the other, or `--ratio <n>` times as far.
+pub fn part_one(input: &str) -> Option<u32> {
+    let ratio = option("--ratio").unwrap_or(2);
+    Some(count_antinodes(input, Harmonics::Ratio(ratio)) as u32)
 }
 
 fn shared_setup(input: &str) -> HashMap<char, Vec<Coord>> {
@@ -110,37 +205,7 @@ fn shared_setup(input: &str) -> HashMap<char, Vec<Coord>> {
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let nodes_grouped_by_character = shared_setup(input);
-    let x_bounds = 0..input.lines().next().unwrap().chars().count() as isize;
-    let y_bounds = 0..input.lines().count() as isize;
-    let mut antinodes = HashSet::new();
-
-    // Now that the nodes are grouped by letter, we can iterate over each combination of coords for a given "frequnecy" (character)
-    for (_character, coords) in nodes_grouped_by_character {
-        for (a, b) in coords.iter().tuple_combinations() {
-            // Calculate Manhattan distance between the two nodes
-            let delta = *b - *a; // The vectored distance between the two nodes
-                                 // Keep checking every node in that vector to see if it's an antinode
-            let mut potential_antinode = *a;
-            while x_bounds.contains(&potential_antinode.x)
-                && y_bounds.contains(&potential_antinode.y)
-            {
-                antinodes.insert(potential_antinode);
-                potential_antinode += delta;
-            }
-
-            let delta = -delta;
-            let mut potential_antinode = *a;
-            while x_bounds.contains(&potential_antinode.x)
-                && y_bounds.contains(&potential_antinode.y)
-            {
-                antinodes.insert(potential_antinode);
-                potential_antinode += delta;
-            }
-        }
-    }
-
-    Some(antinodes.len() as u32)
+    Some(count_antinodes(input, Harmonics::AnyMultiple) as u32)
 }
 
 #[cfg(test)]
@@ -158,4 +223,46 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(34));
     }
+
+    #[test]
+    fn test_harmonics() {
+        let antinodes = |harmonics, reduce| Antinodes {
+            harmonics,
+            reduce,
+            width: 10,
+            height: 10,
+        };
+        let (a, b) = (Coord { x: 1, y: 1 }, Coord { x: 4, y: 4 });
+        let found = |harmonics, reduce| {
+            let found: Vec<Coord> = antinodes(harmonics, reduce).of_pair(a, b);
+            found.into_iter().map(|c| (c.x, c.y)).sorted().collect_vec()
+        };
+        assert_eq!(found(Harmonics::Ratio(2), false), [(7, 7)]);
+        assert_eq!(found(Harmonics::Ratio(2), true), [(2, 2), (3, 3), (7, 7)]);
+        assert_eq!(
+            found(Harmonics::AnyMultiple, false),
+            [(1, 1), (4, 4), (7, 7)]
+        );
+        assert_eq!(found(Harmonics::AnyMultiple, true).len(), 10);
+
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let antennas = shared_setup(&input);
+        let antinodes = Antinodes::new(&input, Harmonics::Ratio(2)).find(&antennas);
+        let view = overlay(&input, &antennas, &antinodes);
+        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let cells = (0..grid.len()).flat_map(|y| (0..grid[y].len()).map(move |x| (y, x)));
+        let highlighted: HashSet<Coord> = cells
+            .filter(|&pos| view.cell(pos).unwrap().bg == Some(Color::Blue))
+            .map(|(y, x)| Coord {
+                x: x as isize,
+                y: y as isize,
+            })
+            .collect();
+        assert_eq!(highlighted, antinodes);
+        for antinode in &antinodes {
+            let (y, x) = (antinode.y as usize, antinode.x as usize);
+            let expected = if grid[y][x] == '.' { '#' } else { grid[y][x] };
+            assert_eq!(view.cell((y, x)).unwrap().ch, expected);
+        }
+    }
 }
@@ -1,23 +1,17 @@
-use std::{collections::VecDeque, fmt::Display, ops::Deref};
+use std::{collections::VecDeque, fmt::Display};
 
-use itertools::Itertools;
-use nom::{bytes::complete::take, combinator::map_res as map_result, multi::many1, IResult};
+use advent_of_code::{
+    interval::{Fit, IntervalSet},
+    recorder::Recorder,
+    render::{visualize_requested, Color, GridRenderer},
+};
 
 advent_of_code::solution!(9);
 
-fn parse_digit(input: &str) -> IResult<&str, u32> {
-    map_result(take(1usize), |digit: &str| digit.parse::<u32>())(input)
-}
-
-fn parse_digits(input: &str) -> IResult<&str, Vec<u32>> {
-    many1(parse_digit)(input)
-}
-
 #[derive(Debug, Clone, Copy)]
 struct DriveFileLayout {
     blocks_occupied: usize,
     id: Option<usize>,
-    moved: bool,
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
@@ -31,13 +25,11 @@ pub fn part_one(input: &str) -> Option<usize> {
                 DriveFileLayout {
                     blocks_occupied,
                     id: None,
-                    moved: false,
                 }
             } else {
                 DriveFileLayout {
                     blocks_occupied,
                     id: Some(i / 2),
-                    moved: false,
                 }
             }
         })
@@ -45,7 +37,7 @@ pub fn part_one(input: &str) -> Option<usize> {
     // dbg!(&numbers);
     let mut queue: VecDeque<usize> = files
         .iter()
-        .flat_map(|file| std::iter::repeat(file.id).take(file.blocks_occupied))
+        .flat_map(|file| std::iter::repeat_n(file.id, file.blocks_occupied))
         .flatten()
         .collect();
     // dbg!(&queue);
@@ -64,124 +56,178 @@ pub fn part_one(input: &str) -> Option<usize> {
     }
     // dbg!(&drive);
 
-    Some(drive.iter().enumerate().map(|(i, &id)| (i * id)).sum())
+    Some(drive.iter().enumerate().map(|(i, &id)| i * id).sum())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct FileBlock {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileSpan {
     id: usize,
+    start: usize,
     length: usize,
 }
-impl FileBlock {
-    fn to_string(&self) -> String {
-        format!("{}", self.id)
+
+/// How scattered the free space between files is after compaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fragmentation {
+    moved: usize,
+    free_blocks: usize,
+    free_spans: usize,
+    largest_span: usize,
+}
+impl Fragmentation {
+    /// The share of free blocks outside the largest free span, from 0 (all in one span) to 1.
+    fn ratio(&self) -> f64 {
+        match self.free_blocks {
+            0 => 0.0,
+            total => 1.0 - self.largest_span as f64 / total as f64,
+        }
+    }
+}
+impl Display for Fragmentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} files moved, {} free blocks in {} spans (largest {}), {:.1}% fragmented",
+            self.moved,
+            self.free_blocks,
+            self.free_spans,
+            self.largest_span,
+            self.ratio() * 100.0
+        )
     }
 }
 
 #[derive(Debug, Clone)]
 struct DiskLayout {
-    blocks: VecDeque<Option<FileBlock>>,
-    max_id: usize,
+    /// Every file, in id order.
+    files: Vec<FileSpan>,
+    /// The free blocks, indexed by span length.
+    free: IntervalSet<usize>,
 }
 
 impl DiskLayout {
     fn new(disk_map: &str) -> Self {
-        let mut blocks = VecDeque::new();
-        let mut max_id = 0;
-        // Parse the disk map
-        disk_map.chars().enumerate().peekable().for_each(|(i, c)| {
+        let mut files = vec![];
+        let mut free = IntervalSet::new();
+        let mut start = 0;
+        for (i, c) in disk_map.chars().enumerate() {
             let length = c.to_digit(10).unwrap() as usize;
-            // If it's an even index, it's a file...
+            // Even indices are files, odd ones free space.
             if i % 2 == 0 {
-                if c.is_digit(10) {
-                    blocks.extend(
-                        std::iter::repeat(Some(FileBlock { id: i / 2, length })).take(length),
-                    );
-                }
-            // If it's an odd index, it's a free space...
+                files.push(FileSpan {
+                    id: i / 2,
+                    start,
+                    length,
+                });
             } else {
-                blocks.extend(std::iter::repeat(None).take(length));
+                free.insert(start..start + length);
             }
-            max_id = i / 2;
-        });
-
-        DiskLayout { blocks, max_id }
+            start += length;
+        }
+        DiskLayout { files, free }
     }
 
-    fn compact_files(&mut self) {
-        for current_id in (0..=self.max_id).rev() {
-            // println!("compacting file with id: {}", current_id);
-            // Starting from the end of the disk, find the first file with the current id
-            if let Some((file_index, file)) = self
-                .blocks
-                .iter()
-                .enumerate()
-                .rev()
-                .find(|(_, block)| block.is_some() && block.as_ref().unwrap().id == current_id)
-            {
-                let space_required = file.as_ref().unwrap().length;
-
-                // Return the positions of the first consecutive free spaces in the disk, iterating over chunks of size space_required
-                let mut positions = Vec::<usize>::with_capacity(space_required);
-                for (i, block) in self.blocks.iter().enumerate() {
-                    if block.is_none() && i < file_index {
-                        positions.push(i);
-                        if positions.len() == space_required {
-                            break;
-                        }
-                    } else {
-                        positions.clear();
-                    }
-                }
-                if positions.len() == space_required {
-                    for position in positions {
-                        let (file_block_index, _) = self
-                            .blocks
-                            .iter()
-                            .enumerate()
-                            .rev()
-                            .find(|(_, block)| {
-                                block.is_some() && block.as_ref().unwrap().id == current_id
-                            })
-                            .unwrap();
-                        self.blocks.swap(position, file_block_index);
-                    }
-                }
+    /// Moves whole files, highest id first, to the free span `fit` picks among the ones left of
+    /// the file, calling `on_step` after each file. Returns how many files moved.
+    fn compact_files(&mut self, fit: Fit, mut on_step: impl FnMut(&Self)) -> usize {
+        let mut moved = 0;
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id];
+            if let Some(span) = self.free.fit_before(fit, file.length, file.start) {
+                self.free.remove(span.start..span.start + file.length);
+                self.free.insert(file.start..file.start + file.length);
+                self.files[id].start = span.start;
+                moved += 1;
             }
-            // self.print_disk();
+            on_step(self);
+        }
+        moved
+    }
+
+    /// The end of the last file.
+    fn used(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| file.start + file.length)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn fragmentation(&self, moved: usize) -> Fragmentation {
+        let used = self.used();
+        let lengths: Vec<usize> = self
+            .free
+            .iter()
+            .filter(|span| span.start < used)
+            .map(|span| span.end.min(used) - span.start)
+            .collect();
+        Fragmentation {
+            moved,
+            free_blocks: lengths.iter().sum(),
+            free_spans: lengths.len(),
+            largest_span: lengths.iter().copied().max().unwrap_or(0),
         }
     }
 
     fn calculate_checksum(&self) -> usize {
-        self.blocks
+        self.files
             .iter()
-            .enumerate()
-            .filter_map(|(pos, block)| block.as_ref().map(|file_block| pos * file_block.id))
+            .map(|file| (file.start..file.start + file.length).sum::<usize>() * file.id)
             .sum()
     }
-    fn print_disk(&self) {
-        // Print the file block id or a "." if it's a free space
-        println!(
-            "{}",
-            self.blocks
-                .iter()
-                .map(|block| block
-                    .as_ref()
-                    .map(|file_block| file_block.id.to_string())
-                    .unwrap_or(".".to_string()))
-                .join("")
-        );
+
+    /// The file id in every block up to the end of the last file.
+    fn blocks(&self) -> Vec<Option<usize>> {
+        let mut blocks = vec![None; self.used()];
+        for file in &self.files {
+            blocks[file.start..file.start + file.length].fill(Some(file.id));
+        }
+        blocks
+    }
+
+    fn view(&self) -> GridRenderer {
+        // Wrap the disk so it fits on screen: one character per block, colored by file id.
+        const WIDTH: usize = 64;
+        let blocks = self.blocks();
+        let rows = blocks.len().div_ceil(WIDTH);
+        let block = |(row, col): (usize, usize)| blocks.get(row * WIDTH + col).copied();
+        let mut view = GridRenderer::new(rows, WIDTH, |position| match block(position) {
+            Some(Some(id)) => char::from_digit((id % 10) as u32, 10).unwrap(),
+            Some(None) => '.',
+            None => ' ',
+        });
+        let mut by_color = vec![vec![]; Color::ALL.len()];
+        for (i, block) in blocks.iter().enumerate() {
+            if let Some(id) = block {
+                by_color[id % Color::ALL.len()].push((i / WIDTH, i % WIDTH));
+            }
+        }
+        for (i, positions) in by_color.into_iter().enumerate() {
+            view.color(positions, Color::nth(i));
+        }
+        view
     }
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
     let disk_map = input.trim();
     let mut disk = DiskLayout::new(disk_map);
-    // println!("fragmented:");
-    // disk.print_disk();
-    disk.compact_files();
-    // println!("compacted:");
-    // disk.print_disk();
+    if visualize_requested() {
+        for fit in Fit::ALL {
+            let mut disk = disk.clone();
+            let moved = disk.compact_files(fit, |_| {});
+            println!(
+                "{fit:<9}  checksum {:>15}  {}",
+                disk.calculate_checksum(),
+                disk.fragmentation(moved)
+            );
+        }
+    }
+    let mut recorder = Recorder::from_args().every(disk.files.len() / 200);
+    recorder.capture_with(|| disk.view());
+    disk.compact_files(Fit::First, |disk| recorder.capture_with(|| disk.view()));
+    recorder.capture_last_with(|| disk.view());
+    recorder.finish(DAY, 2);
     let checksum = disk.calculate_checksum();
     Some(checksum)
 }
@@ -201,4 +247,28 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2858));
     }
+
+    #[test]
+    fn test_fit_strategies() {
+        // A two-block file that fits a three-block span first and a two-block span best.
+        let disk = DiskLayout::new("1312102");
+        let results = Fit::ALL.map(|fit| {
+            let mut disk = disk.clone();
+            let moved = disk.compact_files(fit, |_| {});
+            let layout: String = disk
+                .blocks()
+                .iter()
+                .map(|block| block.map_or('.', |id| char::from_digit(id as u32, 10).unwrap()))
+                .collect();
+            (layout, disk.fragmentation(moved))
+        });
+        let layouts = results.each_ref().map(|(layout, _)| layout.as_str());
+        assert_eq!(layouts, ["03321", "021..33", "0331.2"]);
+        let [first, best, _] = results.map(|(_, stats)| stats);
+        assert_eq!(
+            first.to_string(),
+            "2 files moved, 0 free blocks in 0 spans (largest 0), 0.0% fragmented"
+        );
+        assert_eq!((best.moved, best.free_blocks, best.largest_span), (3, 2, 2));
+    }
 }
@@ -1,204 +1,147 @@
+use advent_of_code::{
+    image::{render_path, render_requested, Image, Rgb},
+    pathfind::{GridGraph, Pos},
+    render::{visualize_requested, Color},
+};
 use grid::Grid;
 use itertools::Itertools;
-use rayon::iter::{ParallelBridge, ParallelIterator};
-use std::{
-    collections::{HashSet, VecDeque},
-    ops::Add,
-    rc::Rc,
-    sync::{Arc, Mutex},
-};
+use rayon::prelude::*;
+use std::collections::HashSet;
 
 advent_of_code::solution!(10);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Coord {
-    row: isize,
-    col: isize,
-    value: u32,
-}
-impl Add<Coord> for Coord {
-    type Output = Coord;
-    fn add(self, other: Coord) -> Coord {
-        Coord {
-            row: self.row + other.row,
-            col: self.col + other.col,
-            value: self.value,
-        }
-    }
-}
-impl Coord {
-    fn get_adjacent_perpendicular_neighbors(&self, grid: &Grid<u32>) -> Vec<Coord> {
-        let mut neighbors = Vec::new();
-        let down = *self + Direction::Down.to_coord();
-        let up = *self + Direction::Up.to_coord();
-        let left = *self + Direction::Left.to_coord();
-        let right = *self + Direction::Right.to_coord();
-
-        if let Some(v) = grid.get(down.row, down.col) {
-            neighbors.push(Coord {
-                row: down.row,
-                col: down.col,
-                value: *v,
-            });
-        }
-        if let Some(v) = grid.get(up.row, up.col) {
-            neighbors.push(Coord {
-                row: up.row,
-                col: up.col,
-                value: *v,
-            });
-        }
-        if let Some(v) = grid.get(left.row, left.col) {
-            neighbors.push(Coord {
-                row: left.row,
-                col: left.col,
-                value: *v,
-            });
-        }
-        if let Some(v) = grid.get(right.row, right.col) {
-            neighbors.push(Coord {
-                row: right.row,
-                col: right.col,
-                value: *v,
-            });
-        }
-
-        neighbors
-    }
-}
-#[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-impl Direction {
-    fn to_coord(&self) -> Coord {
-        match self {
-            Direction::Up => Coord {
-                row: 0,
-                col: -1,
-                value: 0,
-            },
-            Direction::Down => Coord {
-                row: 0,
-                col: 1,
-                value: 0,
-            },
-            Direction::Left => Coord {
-                row: -1,
-                col: 0,
-                value: 0,
-            },
-            Direction::Right => Coord {
-                row: 1,
-                col: 0,
-                value: 0,
-            },
-        }
-    }
-}
-
-#[derive(Debug)]
-struct QueueItem {
-    coord: Coord,
-    path: Vec<Coord>,
-}
-
-pub fn shared(input: &str, find_all_paths: bool) -> Vec<Vec<Coord>> {
+fn parse(input: &str) -> Grid<u32> {
     let cols = input.lines().next().unwrap().len();
     let input = input
         .trim()
         .chars()
         .filter_map(|c| c.to_digit(10))
         .collect_vec();
-    let grid = Grid::from_vec(input, cols);
-    let mut all_paths = Vec::new();
-
-    // Breadth-first search approach
-    grid.indexed_iter().for_each(|(coord, v)| {
-        if v == &0 {
-            let mut queue = VecDeque::new();
-            let coord = Coord {
-                row: coord.0 as isize,
-                col: coord.1 as isize,
-                value: *v,
-            };
-
-            queue.push_back(QueueItem {
-                coord,
-                path: vec![coord],
-            });
+    Grid::from_vec(input, cols)
+}
 
-            let mut visited = HashSet::new();
-            if !find_all_paths {
-                visited.insert(coord);
-            }
+/// Trails only ever go up by exactly one.
+fn hiking_trails(grid: &Grid<u32>) -> GridGraph<'_, u32, impl Fn(&u32, &u32) -> bool> {
+    GridGraph::new(grid, |&from, &to| to == from + 1)
+}
 
-            while let Some(item) = queue.pop_front() {
-                // println!("Current item: {:?}", item);
-                // If we've reached 9, we've found a complete path
-                if item.coord.value == 9 {
-                    // println!("Found complete path: {:?}", item.path);
-                    all_paths.push(item.path);
-                    continue;
-                }
-                // Look for the next number in sequence
-                let target_value = item.coord.value + 1;
+fn trailheads(grid: &Grid<u32>) -> impl Iterator<Item = Pos> + '_ {
+    grid.indexed_iter()
+        .filter(|(_, &height)| height == 0)
+        .map(|(pos, _)| pos)
+}
 
-                let neighbors = item.coord.get_adjacent_perpendicular_neighbors(&grid);
-                // println!("Neighbors: {:?}", neighbors);
-                for neighbor in neighbors {
-                    // println!(
-                    //     "Neighbor ({}) == target_value ({}): {:?}",
-                    //     neighbor.value,
-                    //     target_value,
-                    //     neighbor.value == target_value
-                    // );
-                    // println!("Neighbor visited: {:?}", visited.contains(&neighbor));
+/// For every position, the number of distinct trails from it up to a 9. Filled in from the top
+/// down, so each count only adds up the counts one level higher.
+fn trail_counts(grid: &Grid<u32>) -> Grid<usize> {
+    let trails = hiking_trails(grid);
+    let mut counts = Grid::new(grid.rows(), grid.cols());
+    let by_height = grid
+        .indexed_iter()
+        .sorted_by_key(|(_, &height)| std::cmp::Reverse(height));
+    for (pos, &height) in by_height {
+        counts[pos] = match height {
+            9 => 1,
+            _ => trails.neighbors(pos).iter().map(|&next| counts[next]).sum(),
+        };
+    }
+    counts
+}
 
-                    if neighbor.value == target_value {
-                        if !find_all_paths && !visited.contains(&neighbor) {
-                            visited.insert(neighbor);
-                            let mut new_path = item.path.clone();
-                            new_path.push(neighbor);
-                            queue.push_back(QueueItem {
-                                coord: neighbor,
-                                path: new_path,
-                            });
-                        } else if find_all_paths {
-                            let mut new_path = item.path.clone();
-                            new_path.push(neighbor);
-                            queue.push_back(QueueItem {
-                                coord: neighbor,
-                                path: new_path,
-                            });
-                        }
-                        // println!("Pushing neighbor to queue: {:?}", neighbor);
-                    }
-                }
-                // println!("Queue:");
-                // for item in queue.iter() {
-                //     println!("path (length {}): {:?}", item.path.len(), item.path);
-                // }
+/// Every trail from `start`, only following positions that still lead to a 9.
+fn list_trails(grid: &Grid<u32>, counts: &Grid<usize>, start: Pos) -> Vec<Vec<Pos>> {
+    let trails = hiking_trails(grid);
+    let mut found = vec![];
+    let mut stack = vec![vec![start]];
+    while let Some(trail) = stack.pop() {
+        let last = *trail.last().unwrap();
+        if grid[last] == 9 {
+            found.push(trail);
+            continue;
+        }
+        for next in trails.neighbors(last) {
+            if counts[next] > 0 {
+                let mut longer = trail.clone();
+                longer.push(next);
+                stack.push(longer);
             }
         }
-    });
-    // let guard = all_paths.lock().unwrap();
-    // let paths = guard.to_vec();
-    all_paths
+    }
+    found
+}
+
+/// Draws the heights from dark to light, tinting every position on a trail green.
+fn render(grid: &Grid<u32>, trails: &HashSet<Pos>) -> Image {
+    let colors = grid
+        .iter()
+        .map(|&height| Rgb::BLACK.lerp(Rgb::WHITE, height as f64 / 12.0));
+    let mut colors = Grid::from_vec(colors.collect(), grid.cols());
+    let trail = Rgb::from(Color::Green);
+    for &pos in trails {
+        colors[pos] = colors[pos].lerp(trail, 0.6);
+    }
+    Image::from_grid(&colors, 8, |&color| color)
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
-    let all_paths = shared(input, false);
-    Some(all_paths.len())
+    let grid = parse(input);
+    let trails = hiking_trails(&grid);
+    let starts: Vec<Pos> = trailheads(&grid).collect();
+    let reachable: Vec<HashSet<Pos>> = starts
+        .par_iter()
+        .map(|&start| trails.reachable(start))
+        .collect();
+    if render_requested() {
+        let path = render_path(DAY, 1);
+        // Dead ends are reachable too, only tint positions that still lead to a 9.
+        let counts = trail_counts(&grid);
+        let on_trail = reachable
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&pos| counts[pos] > 0)
+            .collect();
+        match render(&grid, &on_trail).save(&path) {
+            Ok(()) => println!("Rendered trails to \"{}\"", path.display()),
+            Err(e) => eprintln!("Failed to render trails: {e}"),
+        }
+    }
+    let score = reachable
+        .iter()
+        .map(|positions| positions.iter().filter(|&&pos| grid[pos] == 9).count())
+        .sum();
+    Some(score)
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    let all_paths = shared(input, true);
-    let ratings = all_paths.len();
-    Some(ratings)
+    let grid = parse(input);
+    let counts = trail_counts(&grid);
+    let starts: Vec<Pos> = trailheads(&grid).collect();
+    let rating = starts.iter().map(|&start| counts[start]).sum();
+    if visualize_requested() || render_requested() {
+        // List the trails of the best-rated trailhead.
+        if let Some(&best) = starts.iter().max_by_key(|&&start| counts[start]) {
+            let trails = list_trails(&grid, &counts, best);
+            if visualize_requested() {
+                println!("{} trails from {best:?}:", trails.len());
+                for trail in &trails {
+                    println!(
+                        "  {}",
+                        trail.iter().map(|pos| format!("{pos:?}")).join(" -> ")
+                    );
+                }
+            }
+            if render_requested() {
+                let path = render_path(DAY, 2);
+                let on_trail = trails.into_iter().flatten().collect();
+                match render(&grid, &on_trail).save(&path) {
+                    Ok(()) => println!("Rendered trails to \"{}\"", path.display()),
+                    Err(e) => eprintln!("Failed to render trails: {e}"),
+                }
+            }
+        }
+    }
+    Some(rating)
 }
 
 #[cfg(test)]
@@ -216,4 +159,20 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(81));
     }
+
+    #[test]
+    fn test_counts_and_lists_trails() {
+        let grid = parse(&advent_of_code::template::read_file("examples", DAY));
+        let counts = trail_counts(&grid);
+        let trails = hiking_trails(&grid);
+        for start in trailheads(&grid) {
+            let listed = list_trails(&grid, &counts, start);
+            assert_eq!(listed.len(), counts[start]);
+            assert_eq!(
+                counts[start],
+                trails.count_paths(start, |pos| grid[pos] == 9)
+            );
+            assert!(listed.iter().all(|trail| trail.len() == 10));
+        }
+    }
 }
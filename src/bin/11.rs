@@ -1,60 +1,160 @@
-use cached::proc_macro::cached;
+use std::{cell::RefCell, ops::AddAssign, str::FromStr};
+
+use advent_of_code::{
+    counter::{Counter, Evolver},
+    num::{digit_count, split_at_digit, Modular},
+    render::visualize_requested,
+    template::option,
+};
 use itertools::Itertools;
-use std::collections::HashMap;
+use num_bigint::BigUint;
 
 advent_of_code::solution!(11);
 
-pub fn part_one(input: &str) -> Option<usize> {
-    shared_logic(input.to_string(), 25)
+/// One way a stone can change when you blink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    /// A stone engraved with `from` becomes `to`.
+    Replace { from: u64, to: u64 },
+    /// A stone with an even number of digits splits into its left and right halves.
+    SplitEvenDigits,
+    /// Any stone is multiplied by the factor.
+    Multiply(u64),
 }
 
-#[cached]
-fn split(stone: usize) -> Vec<usize> {
-    if stone == 0 {
-        return vec![1];
-    }
-    let len = stone.ilog10() + 1;
-    if len % 2 == 0 {
-        let split = 10usize.pow(len / 2);
-        vec![stone / split, stone % split]
-    } else {
-        vec![stone * 2024]
+/// Rules tried in order, the first one that applies changes the stone. Written as a
+/// comma-separated list, the puzzle's rules are `0=1,split,*2024`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules(Vec<Rule>);
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self(vec![
+            Rule::Replace { from: 0, to: 1 },
+            Rule::SplitEvenDigits,
+            Rule::Multiply(2024),
+        ])
     }
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
-    shared_logic(input.to_string(), 75)
+impl FromStr for Rules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = |rule: &str| {
+            let number = |n: &str| n.parse().map_err(|_| format!("invalid rule \"{rule}\""));
+            if rule == "split" {
+                Ok(Rule::SplitEvenDigits)
+            } else if let Some(factor) = rule.strip_prefix('*') {
+                Ok(Rule::Multiply(number(factor)?))
+            } else if let Some((from, to)) = rule.split_once('=') {
+                Ok(Rule::Replace {
+                    from: number(from)?,
+                    to: number(to)?,
+                })
+            } else {
+                Err(format!("invalid rule \"{rule}\""))
+            }
+        };
+        s.split(',').map(|r| rule(r.trim())).try_collect().map(Self)
+    }
 }
 
-fn shared_logic(input: String, blinks: usize) -> Option<usize> {
-    let split_numbers = input.split_ascii_whitespace().collect_vec();
-    let stones = split_numbers
-        .iter()
-        .filter_map(|&s| s.parse::<usize>().ok())
-        .collect_vec();
-    let mut current = stones
-        .iter()
-        .map(|&s| (s, 1))
-        .collect::<HashMap<usize, usize>>();
-    for _ in 0..blinks {
-        let mut next: HashMap<usize, usize> = HashMap::new();
-        for (stone, count) in current {
-            for new_stone in split(stone) {
-                next.entry(new_stone)
-                    .and_modify(|c| *c += count)
-                    .or_insert(count);
+impl Rules {
+    /// What `stone` turns into after one blink. Stones no rule applies to stay the same.
+    fn blink(&self, &stone: &u64) -> Result<Vec<u64>, String> {
+        for rule in &self.0 {
+            match *rule {
+                Rule::Replace { from, to } if stone == from => return Ok(vec![to]),
+                Rule::SplitEvenDigits if digit_count(stone) % 2 == 0 => {
+                    let (left, right) = split_at_digit(stone, digit_count(stone) / 2);
+                    return Ok(vec![left, right]);
+                }
+                Rule::Multiply(factor) => {
+                    return match stone.checked_mul(factor) {
+                        Some(product) => Ok(vec![product]),
+                        None => Err(format!("stone {stone} times {factor} overflows a u64")),
+                    }
+                }
+                _ => {}
             }
         }
-        current = next;
+        Ok(vec![stone])
+    }
+}
+
+fn parse(input: &str) -> Vec<u64> {
+    input
+        .split_ascii_whitespace()
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+/// The number of stones before the first blink and after every blink up to `blinks`, counting
+/// every initial stone as `one`. Fails if a stone number overflows.
+fn blink_series<C>(stones: &[u64], rules: &Rules, blinks: usize, one: C) -> Result<Vec<C>, String>
+where
+    C: Clone + Default + AddAssign,
+{
+    let mut counter = Counter::new();
+    for &stone in stones {
+        counter.add(stone, one.clone());
+    }
+    let initial = counter.total();
+    // The evolver's rule cannot fail, so the first error is kept aside and checked at the end.
+    let error = RefCell::new(None);
+    let mut evolver = Evolver::new(|stone: &u64| {
+        rules.blink(stone).unwrap_or_else(|e| {
+            error.borrow_mut().get_or_insert(e);
+            vec![]
+        })
+    })
+    .memoized()
+    .with_stats();
+    evolver.run(counter, blinks);
+    if let Some(e) = error.take() {
+        return Err(e);
     }
-    Some(current.values().sum::<usize>())
+    let totals = evolver.stats().iter().map(|stats| stats.total.clone());
+    Ok(std::iter::once(initial).chain(totals).collect())
+}
+
+pub fn part_one(input: &str) -> Option<u128> {
+    blink_series(&parse(input), &Rules::default(), 25, 1u128)
+        .inspect_err(|e| eprintln!("{e}"))
+        .ok()?
+        .last()
+        .copied()
+}
+
+/// Blinks 75 times with the puzzle's rules, unless overridden with `--blinks <n>` and
+/// `--rules <rules>`. Counts are exact, or modulo `--modulo <prime>` if given.
+pub fn part_two(input: &str) -> Option<BigUint> {
+    let stones = parse(input);
+    let blinks = option("--blinks").unwrap_or(75);
+    let rules = match option::<String>("--rules") {
+        Some(rules) => rules.parse().inspect_err(|e| eprintln!("{e}")).ok()?,
+        None => Rules::default(),
+    };
+    let series: Result<Vec<BigUint>, String> = match option("--modulo") {
+        Some(0) => Err("--modulo must be positive".to_string()),
+        Some(modulus) => blink_series(&stones, &rules, blinks, Modular::new(1, modulus))
+            .map(|series| series.iter().map(|count| count.value().into()).collect()),
+        None => blink_series(&stones, &rules, blinks, BigUint::from(1u8)),
+    };
+    let series = series.inspect_err(|e| eprintln!("{e}")).ok()?;
+    if visualize_requested() {
+        for (blink, count) in series.iter().enumerate() {
+            println!("{blink:>5}  {count}");
+        }
+    }
+    series.last().cloned()
 }
 
 #[cfg(test)]
 mod tests {
-    // use std::error::Error;
-
     use super::*;
+    use advent_of_code::crosscheck::CrossCheck;
 
     #[test]
     fn test_part_one() {
@@ -65,6 +165,64 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(228651922369703));
+        assert_eq!(result, Some(BigUint::from(65601038650482u64)));
+    }
+
+    #[test]
+    fn test_series_and_rules() {
+        let stones = parse(&advent_of_code::template::read_file("examples", DAY));
+        let exact = blink_series(&stones, &Rules::default(), 6, 1u64).unwrap();
+        assert_eq!(exact, [2, 3, 4, 5, 9, 13, 22]);
+
+        // Far past where the counts overflow a u128.
+        let prime = 1_000_000_007;
+        let modular =
+            blink_series(&stones, &Rules::default(), 300, Modular::new(1, prime)).unwrap();
+        let big = blink_series(&stones, &Rules::default(), 300, BigUint::from(1u8)).unwrap();
+        assert!(big[300].bits() > 128);
+        assert_eq!(BigUint::from(modular[300].value()), &big[300] % prime);
+
+        let rules: Rules = "0=1,split,*2024".parse().unwrap();
+        assert_eq!(rules, Rules::default());
+        assert_eq!(rules.blink(&1000), Ok(vec![10, 0]));
+        assert!("0=1,double".parse::<Rules>().is_err());
+
+        let huge: Rules = "*1000000000000".parse().unwrap();
+        assert!(blink_series(&stones, &huge, 1, 1u64).is_ok());
+        assert!(blink_series(&stones, &huge, 2, 1u64).is_err());
+    }
+
+    /// Blinks 25 times keeping every stone in a list, with the puzzle's rules written out
+    /// rather than taken from [`Rules`].
+    fn literal_stones(input: &str) -> Option<u128> {
+        let mut stones: Vec<u64> = input
+            .split_whitespace()
+            .map(|stone| stone.parse().unwrap())
+            .collect();
+        for _ in 0..25 {
+            stones = stones
+                .iter()
+                .flat_map(|&stone| {
+                    let digits = stone.to_string();
+                    if stone == 0 {
+                        vec![1]
+                    } else if digits.len() % 2 == 0 {
+                        let (left, right) = digits.split_at(digits.len() / 2);
+                        vec![left.parse().unwrap(), right.parse().unwrap()]
+                    } else {
+                        vec![stone * 2024]
+                    }
+                })
+                .collect();
+        }
+        Some(stones.len() as u128)
+    }
+
+    #[test]
+    fn test_part_one_matches_literal_stones() {
+        CrossCheck::new(DAY)
+            .cases(20)
+            .sizes(1..4)
+            .run(part_one, literal_stones);
     }
 }
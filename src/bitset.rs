@@ -0,0 +1,273 @@
+//! Dense sets of grid positions (or positions plus a direction), as fast replacements for
+//! `HashSet<(usize, usize)>` in grid simulations.
+//!
+//! [`GridSet`] stores a generation stamp per key, so clearing it is O(1): useful when the same set
+//! is refilled thousands of times, like when trying every obstacle position. [`GridBitSet`] stores
+//! a single bit per key, for when memory matters more than clearing.
+//!
+//! Positions are `(row, col)` indices, the same as `grid::Grid` and [`Dir4::step`].
+use std::marker::PhantomData;
+
+use crate::direction::Dir4;
+
+/// A key that maps to a unique index in `0..Self::per_cell() * rows * cols`.
+pub trait DenseKey: Sized {
+    /// How many keys share one grid cell.
+    fn per_cell() -> usize;
+    /// The index of the key, or [`None`] if it is outside a `rows` by `cols` grid.
+    fn to_index(&self, rows: usize, cols: usize) -> Option<usize>;
+    fn from_index(index: usize, cols: usize) -> Self;
+}
+
+impl DenseKey for (usize, usize) {
+    fn per_cell() -> usize {
+        1
+    }
+
+    fn to_index(&self, rows: usize, cols: usize) -> Option<usize> {
+        (self.0 < rows && self.1 < cols).then(|| self.0 * cols + self.1)
+    }
+
+    fn from_index(index: usize, cols: usize) -> Self {
+        (index / cols, index % cols)
+    }
+}
+
+impl DenseKey for ((usize, usize), Dir4) {
+    fn per_cell() -> usize {
+        4
+    }
+
+    fn to_index(&self, rows: usize, cols: usize) -> Option<usize> {
+        Some(self.0.to_index(rows, cols)? * 4 + self.1 as usize)
+    }
+
+    fn from_index(index: usize, cols: usize) -> Self {
+        (
+            <(usize, usize)>::from_index(index / 4, cols),
+            Dir4::ALL[index % 4],
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A set of keys on a `rows` by `cols` grid, with O(1) insert, lookup and clear.
+///
+/// ```
+/// # use advent_of_code::{bitset::GridSet, direction::Dir4};
+/// let mut visited = GridSet::new(10, 10);
+/// assert!(visited.insert(((2, 3), Dir4::North)));
+/// assert!(!visited.insert(((2, 3), Dir4::North)));
+/// assert!(!visited.contains(&((2, 3), Dir4::East)));
+/// visited.clear();
+/// assert!(visited.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridSet<K> {
+    rows: usize,
+    cols: usize,
+    /// The generation each key was last inserted in; a key is in the set if it matches.
+    stamps: Vec<u32>,
+    generation: u32,
+    len: usize,
+    key: PhantomData<K>,
+}
+
+impl<K: DenseKey> GridSet<K> {
+    /// Creates an empty set for a `rows` by `cols` grid.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            stamps: vec![0; rows * cols * K::per_cell()],
+            generation: 1,
+            len: 0,
+            key: PhantomData,
+        }
+    }
+
+    /// Adds `key`, returning whether it was new.
+    ///
+    /// # Panics
+    ///
+    /// If `key` is outside the grid.
+    pub fn insert(&mut self, key: K) -> bool {
+        let index = key.to_index(self.rows, self.cols);
+        let stamp = &mut self.stamps[index.expect("key outside of the grid")];
+        if *stamp == self.generation {
+            return false;
+        }
+        *stamp = self.generation;
+        self.len += 1;
+        true
+    }
+
+    /// Whether `key` is in the set. Keys outside the grid never are.
+    pub fn contains(&self, key: &K) -> bool {
+        key.to_index(self.rows, self.cols)
+            .is_some_and(|index| self.stamps[index] == self.generation)
+    }
+
+    /// Removes every key by starting a new generation.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.generation = match self.generation.checked_add(1) {
+            Some(generation) => generation,
+            None => {
+                self.stamps.fill(0);
+                1
+            }
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the keys in index order. Takes time proportional to the grid size.
+    pub fn iter(&self) -> impl Iterator<Item = K> + '_ {
+        self.stamps
+            .iter()
+            .enumerate()
+            .filter(|(_, &stamp)| stamp == self.generation)
+            .map(|(index, _)| K::from_index(index, self.cols))
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A set of keys on a `rows` by `cols` grid, stored as one bit per key.
+///
+/// ```
+/// # use advent_of_code::bitset::GridBitSet;
+/// let mut seen = GridBitSet::new(3, 3);
+/// seen.insert((0, 1));
+/// seen.insert((2, 2));
+/// assert!(!seen.contains(&(0, 3)));
+/// assert_eq!(seen.len(), 2);
+/// seen.remove(&(0, 1));
+/// assert_eq!(seen.iter().collect::<Vec<_>>(), [(2, 2)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridBitSet<K> {
+    rows: usize,
+    cols: usize,
+    words: Vec<u64>,
+    key: PhantomData<K>,
+}
+
+impl<K: DenseKey> GridBitSet<K> {
+    /// Creates an empty set for a `rows` by `cols` grid.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            words: vec![0; (rows * cols * K::per_cell()).div_ceil(64)],
+            key: PhantomData,
+        }
+    }
+
+    fn index(&self, key: &K) -> Option<(usize, u64)> {
+        let index = key.to_index(self.rows, self.cols)?;
+        Some((index / 64, 1 << (index % 64)))
+    }
+
+    /// Adds `key`, returning whether it was new.
+    ///
+    /// # Panics
+    ///
+    /// If `key` is outside the grid.
+    pub fn insert(&mut self, key: K) -> bool {
+        let (word, bit) = self.index(&key).expect("key outside of the grid");
+        let new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        new
+    }
+
+    /// Removes `key`, returning whether it was in the set.
+    pub fn remove(&mut self, key: &K) -> bool {
+        let Some((word, bit)) = self.index(key) else {
+            return false;
+        };
+        let present = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        present
+    }
+
+    /// Whether `key` is in the set. Keys outside the grid never are.
+    pub fn contains(&self, key: &K) -> bool {
+        self.index(key)
+            .is_some_and(|(word, bit)| self.words[word] & bit != 0)
+    }
+
+    /// Removes every key.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Iterates over the keys in index order.
+    pub fn iter(&self) -> impl Iterator<Item = K> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(K::from_index(i * 64 + bit, self.cols))
+            })
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_by_generation() {
+        let mut set: GridSet<(usize, usize)> = GridSet::new(3, 4);
+        set.insert((2, 3));
+        set.insert((0, 1));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [(0, 1), (2, 3)]);
+        assert!(!set.contains(&(3, 0)) && !set.contains(&(0, 4)));
+
+        set.generation = u32::MAX;
+        set.insert((1, 1));
+        set.clear();
+        assert!(!set.contains(&(1, 1)) && set.is_empty());
+        assert!(set.insert((1, 1)));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn packs_positions_and_directions() {
+        let mut set = GridBitSet::new(5, 20);
+        for row in 0..5 {
+            for direction in [Dir4::West, Dir4::North] {
+                assert!(set.insert(((row, 19), direction)));
+            }
+        }
+        assert!(!set.insert(((4, 19), Dir4::West)));
+        assert_eq!(set.len(), 10);
+        assert!(!set.contains(&((5, 0), Dir4::North)));
+        let keys: Vec<_> = set.iter().take(2).collect();
+        assert_eq!(keys, [((0, 19), Dir4::North), ((0, 19), Dir4::West)]);
+    }
+}
@@ -0,0 +1,255 @@
+//! A multiset that tracks how many of each item there are, for "population" puzzles where
+//! every item evolves independently (lanternfish, day 11's stones, ...).
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::AddAssign;
+
+/// Counts of `K`, using `C` (`usize` by default) for the counts. Counts only need to be
+/// `Clone`, so big or modular integers work too.
+#[derive(Debug, Clone)]
+pub struct Counter<K, C = usize> {
+    counts: HashMap<K, C>,
+}
+
+impl<K: Eq + Hash, C: PartialEq> PartialEq for Counter<K, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.counts == other.counts
+    }
+}
+
+impl<K: Eq + Hash, C: Eq> Eq for Counter<K, C> {}
+
+impl<K, C> Default for Counter<K, C> {
+    fn default() -> Self {
+        Self {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<K, C> Counter<K, C>
+where
+    K: Eq + Hash,
+    C: Clone + Default + AddAssign,
+{
+    /// Creates an empty counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `count` copies of `key`.
+    pub fn add(&mut self, key: K, count: C) {
+        *self.counts.entry(key).or_default() += count;
+    }
+
+    /// How many copies of `key` there are.
+    pub fn get(&self, key: &K) -> C {
+        self.counts.get(key).cloned().unwrap_or_default()
+    }
+
+    /// The number of items, counting duplicates.
+    pub fn total(&self) -> C {
+        self.counts.values().fold(C::default(), |mut sum, count| {
+            sum += count.clone();
+            sum
+        })
+    }
+
+    /// The number of distinct items.
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Iterates over each distinct item and its count, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, C)> {
+        self.counts.iter().map(|(key, count)| (key, count.clone()))
+    }
+
+    /// Replaces every item with the items `rule` turns it into, keeping the counts.
+    pub fn transform<I, F>(&self, mut rule: F) -> Self
+    where
+        F: FnMut(&K) -> I,
+        I: IntoIterator<Item = K>,
+    {
+        let mut next = Self::new();
+        for (key, count) in self.iter() {
+            for new_key in rule(key) {
+                next.add(new_key, count.clone());
+            }
+        }
+        next
+    }
+
+    /// Converts the counts to another type, e.g. to widen them before they overflow.
+    pub fn map_counts<D>(self, mut f: impl FnMut(C) -> D) -> Counter<K, D> {
+        Counter {
+            counts: self.counts.into_iter().map(|(k, c)| (k, f(c))).collect(),
+        }
+    }
+}
+
+impl<K, C> FromIterator<K> for Counter<K, C>
+where
+    K: Eq + Hash,
+    C: Clone + Default + AddAssign + From<u8>,
+{
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut counter = Self::new();
+        for key in iter {
+            counter.add(key, C::from(1));
+        }
+        counter
+    }
+}
+
+impl<K, C> Extend<(K, C)> for Counter<K, C>
+where
+    K: Eq + Hash,
+    C: Clone + Default + AddAssign,
+{
+    fn extend<T: IntoIterator<Item = (K, C)>>(&mut self, iter: T) {
+        for (key, count) in iter {
+            self.add(key, count);
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Statistics about one generation produced by an [`Evolver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationStats<C = usize> {
+    /// The generation number, starting at 1 for the result of the first step.
+    pub generation: usize,
+    /// The number of items, counting duplicates.
+    pub total: C,
+    /// The number of distinct items.
+    pub distinct: usize,
+}
+
+/// Repeatedly applies a `K -> impl IntoIterator<Item = K>` rule to a [`Counter`].
+///
+/// ```
+/// # use advent_of_code::counter::{Counter, Evolver};
+/// let fish: Counter<u8> = [3, 4, 3, 1, 2].into_iter().collect();
+/// let spawn = |&timer: &u8| if timer == 0 { vec![6, 8] } else { vec![timer - 1] };
+/// let mut evolver = Evolver::new(spawn).memoized().with_stats();
+/// assert_eq!(evolver.run(fish, 18).total(), 26);
+/// assert_eq!(evolver.stats().len(), 18);
+/// ```
+pub struct Evolver<K, C, F> {
+    rule: F,
+    memo: Option<HashMap<K, Vec<K>>>,
+    stats: Option<Vec<GenerationStats<C>>>,
+}
+
+impl<K, C, F, I> Evolver<K, C, F>
+where
+    K: Eq + Hash + Clone,
+    C: Clone + Default + AddAssign,
+    F: FnMut(&K) -> I,
+    I: IntoIterator<Item = K>,
+{
+    pub fn new(rule: F) -> Self {
+        Self {
+            rule,
+            memo: None,
+            stats: None,
+        }
+    }
+
+    /// Caches the result of the rule for every distinct item it sees.
+    pub fn memoized(mut self) -> Self {
+        self.memo = Some(HashMap::new());
+        self
+    }
+
+    /// Records [`GenerationStats`] after every step.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(vec![]);
+        self
+    }
+
+    /// Applies the rule once.
+    pub fn step(&mut self, counter: &Counter<K, C>) -> Counter<K, C> {
+        let next = match &mut self.memo {
+            Some(memo) => counter.transform(|key| {
+                if let Some(cached) = memo.get(key) {
+                    return cached.clone();
+                }
+                let result: Vec<K> = (self.rule)(key).into_iter().collect();
+                memo.insert(key.clone(), result.clone());
+                result
+            }),
+            None => counter.transform(&mut self.rule),
+        };
+
+        if let Some(stats) = &mut self.stats {
+            stats.push(GenerationStats {
+                generation: stats.len() + 1,
+                total: next.total(),
+                distinct: next.distinct(),
+            });
+        }
+        next
+    }
+
+    /// Applies the rule `generations` times.
+    pub fn run(&mut self, counter: Counter<K, C>, generations: usize) -> Counter<K, C> {
+        (0..generations).fold(counter, |counter, _| self.step(&counter))
+    }
+
+    /// Statistics for every generation so far, or an empty slice unless [`Self::with_stats`] was used.
+    pub fn stats(&self) -> &[GenerationStats<C>] {
+        self.stats.as_deref().unwrap_or_default()
+    }
+
+    /// The number of distinct items the rule has been memoized for.
+    pub fn memo_size(&self) -> usize {
+        self.memo.as_ref().map_or(0, HashMap::len)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_items() {
+        let mut counter: Counter<char> = "hello".chars().collect();
+        counter.extend([('h', 2)]);
+        assert_eq!(counter.get(&'l'), 2);
+        assert_eq!(counter.get(&'h'), 3);
+        assert_eq!(counter.get(&'z'), 0);
+        assert_eq!(counter.total(), 7);
+        assert_eq!(counter.distinct(), 4);
+    }
+
+    #[test]
+    fn evolves_with_and_without_memo() {
+        let rule = |n: &u64| {
+            if n.is_multiple_of(2) {
+                vec![n / 2, n / 2]
+            } else {
+                vec![n + 1]
+            }
+        };
+        let start: Counter<u64, u128> = [8, 3].into_iter().collect();
+
+        let plain = Evolver::new(rule).run(start.clone(), 10);
+        let mut memoized = Evolver::new(rule).memoized().with_stats();
+        assert_eq!(memoized.run(start, 10), plain);
+
+        let stats = memoized.stats();
+        assert_eq!(stats.len(), 10);
+        assert_eq!(stats[9].generation, 10);
+        assert_eq!(stats[9].total, plain.total());
+        assert!(memoized.memo_size() > 0);
+    }
+}
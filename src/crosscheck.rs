@@ -0,0 +1,331 @@
+//! Property tests that run a fast solution against a slow, obviously correct reference.
+//!
+//! A [`CrossCheck`] feeds both implementations random inputs from the day's
+//! [generator](crate::generate). When they disagree, the input is [shrunk](shrink) to one that
+//! still makes them disagree, written to `data/examples/<day>-mismatch.txt` (or another
+//! [directory](CrossCheck::output_dir)) and reported.
+//! Inputs the reference panics on are treated as invalid and skipped.
+use std::fmt::Debug;
+use std::fs;
+use std::ops::Range;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use crate::generate::generator;
+use crate::template::Day;
+
+/// What one implementation made of an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome<T> {
+    Answer(Option<T>),
+    Panicked,
+}
+
+fn outcome<T>(f: impl Fn(&str) -> Option<T>, input: &str) -> Outcome<T> {
+    catch_unwind(AssertUnwindSafe(|| f(input))).map_or(Outcome::Panicked, Outcome::Answer)
+}
+
+/// Compares two implementations of a part on generated inputs.
+///
+/// ```no_run
+/// # use advent_of_code::{crosscheck::CrossCheck, day};
+/// # fn part_one(_: &str) -> Option<u32> { None }
+/// # fn brute_force(_: &str) -> Option<u32> { None }
+/// CrossCheck::new(day!(7)).cases(50).sizes(1..10).run(part_one, brute_force);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    day: Day,
+    cases: usize,
+    sizes: Range<usize>,
+    seed: u64,
+    output_dir: PathBuf,
+}
+
+impl CrossCheck {
+    /// 100 cases of sizes 1 to 20 (in the units of the day's generator), starting at seed 0.
+    pub fn new(day: Day) -> Self {
+        Self {
+            day,
+            cases: 100,
+            sizes: 1..21,
+            seed: 0,
+            output_dir: PathBuf::from("data/examples"),
+        }
+    }
+
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// The sizes to generate, cycled through case by case. Keep them small enough for the
+    /// reference implementation.
+    pub fn sizes(mut self, sizes: Range<usize>) -> Self {
+        self.sizes = sizes;
+        self
+    }
+
+    /// The seed of the first case; case `i` uses `seed + i`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The directory a shrunk failing input is written to, `data/examples` by default.
+    pub fn output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = dir.into();
+        self
+    }
+
+    /// Where a shrunk failing input is written.
+    pub fn mismatch_path(&self) -> PathBuf {
+        self.output_dir.join(format!("{}-mismatch.txt", self.day))
+    }
+
+    /// Runs every case.
+    ///
+    /// # Panics
+    ///
+    /// If the day has no generator, or if `fast` answers differently from `reference` (or panics
+    /// where it does not) on some input. The panic message holds the shrunk input.
+    pub fn run<T: PartialEq + Debug>(
+        &self,
+        fast: impl Fn(&str) -> Option<T>,
+        reference: impl Fn(&str) -> Option<T>,
+    ) {
+        let generator = generator(self.day).expect("no input generator for this day");
+        let mismatch = |input: &str| {
+            let expected = outcome(&reference, input);
+            (expected != Outcome::Panicked)
+                .then(|| outcome(&fast, input))
+                .filter(|actual| *actual != expected)
+                .map(|actual| (actual, expected))
+        };
+        for case in 0..self.cases {
+            let size = self.sizes.start + case % self.sizes.len().max(1);
+            let seed = self.seed + case as u64;
+            let input = generator.generate(size, seed);
+            if mismatch(&input).is_none() {
+                continue;
+            }
+            let shrunk = shrink(&input, |candidate| mismatch(candidate).is_some());
+            let (actual, expected) = mismatch(&shrunk).unwrap();
+            let path = self.mismatch_path();
+            if let Err(e) =
+                fs::create_dir_all(&self.output_dir).and_then(|()| fs::write(&path, &shrunk))
+            {
+                eprintln!("Failed to write \"{}\": {e}", path.display());
+            }
+            panic!(
+                "Day {} does not match the reference for size {size} and seed {seed}, shrunk to \
+                 \"{}\":\n{shrunk}\nfast: {actual:?}\nreference: {expected:?}",
+                self.day,
+                path.display()
+            );
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Shrinks `input` while `fails` holds for it: drops lines, then fields in a line, then lowers
+/// numbers and finally blanks characters of small grids, until none of these helps.
+/// Candidates that no longer parse are fine, as long as `fails` rejects them.
+///
+/// ```
+/// # use advent_of_code::crosscheck::shrink;
+/// let input = "3 8 12\n40 7\n5 100 2\n";
+/// let shrunk = shrink(input, |input| input.split_whitespace().any(|n| n.parse::<u32>().unwrap() > 9));
+/// assert_eq!(shrunk, "10\n");
+/// ```
+pub fn shrink(input: &str, mut fails: impl FnMut(&str) -> bool) -> String {
+    let mut current = input.to_string();
+    loop {
+        let before = current.clone();
+        current = remove_lines(current, &mut fails);
+        current = remove_fields(current, &mut fails);
+        current = lower_numbers(current, &mut fails);
+        current = blank_characters(current, &mut fails);
+        if current == before {
+            return current;
+        }
+    }
+}
+
+fn join_lines(lines: &[&str], trailing_newline: bool) -> String {
+    let mut text = lines.join("\n");
+    if trailing_newline && !lines.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Removes runs of lines, halving the run length down to single lines.
+fn remove_lines(input: String, fails: &mut impl FnMut(&str) -> bool) -> String {
+    let trailing_newline = input.ends_with('\n');
+    let mut lines: Vec<&str> = input.lines().collect();
+    let mut chunk = lines.len() / 2;
+    while chunk > 0 {
+        let mut i = 0;
+        while i < lines.len() {
+            let end = (i + chunk).min(lines.len());
+            let candidate: Vec<&str> = [&lines[..i], &lines[end..]].concat();
+            if fails(&join_lines(&candidate, trailing_newline)) {
+                lines = candidate;
+            } else {
+                i += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    join_lines(&lines, trailing_newline)
+}
+
+/// The byte ranges of the fields of `line`, separated by spaces and commas, each with the
+/// separators that follow it.
+fn fields(line: &str) -> Vec<Range<usize>> {
+    let is_separator = |c: char| c == ' ' || c == ',';
+    let mut fields = vec![];
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        match (start, is_separator(c)) {
+            (None, false) => start = Some(i),
+            (Some(s), false) if i > 0 && is_separator(line[..i].chars().last().unwrap()) => {
+                fields.push(s..i);
+                start = Some(i);
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        fields.push(s..line.len());
+    }
+    fields
+}
+
+/// Removes single fields from each line.
+fn remove_fields(input: String, fails: &mut impl FnMut(&str) -> bool) -> String {
+    let trailing_newline = input.ends_with('\n');
+    let mut lines: Vec<String> = input.lines().map(str::to_string).collect();
+    for row in 0..lines.len() {
+        let mut i = 0;
+        while let Some(field) = fields(&lines[row]).get(i).cloned() {
+            let mut candidate = lines.clone();
+            candidate[row].replace_range(field, "");
+            let len = candidate[row].trim_end_matches([' ', ',']).len();
+            candidate[row].truncate(len);
+            let text: Vec<&str> = candidate.iter().map(String::as_str).collect();
+            if fails(&join_lines(&text, trailing_newline)) {
+                lines = candidate;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    let text: Vec<&str> = lines.iter().map(String::as_str).collect();
+    join_lines(&text, trailing_newline)
+}
+
+/// Replaces every number with a smaller one where possible: 0, 1, half of it or one less.
+fn lower_numbers(mut input: String, fails: &mut impl FnMut(&str) -> bool) -> String {
+    let mut start = 0;
+    while let Some(offset) = input[start..].find(|c: char| c.is_ascii_digit()) {
+        let begin = start + offset;
+        let len = input[begin..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len() - begin);
+        let end = begin + len;
+        if let Ok(n) = input[begin..end].parse::<u64>() {
+            for smaller in [0, 1, n / 2, n.saturating_sub(1)] {
+                if smaller >= n {
+                    continue;
+                }
+                let candidate = format!("{}{smaller}{}", &input[..begin], &input[end..]);
+                if fails(&candidate) {
+                    input = candidate;
+                    break;
+                }
+            }
+        }
+        start = begin
+            + input[begin..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(input.len() - begin);
+    }
+    input
+}
+
+/// Replaces characters of small grids with the most common character if that is a filler like
+/// `.`, rather than a digit or letter that means something.
+fn blank_characters(input: String, fails: &mut impl FnMut(&str) -> bool) -> String {
+    const MAX_LEN: usize = 1000;
+    let width = input.lines().next().map_or(0, str::len);
+    let is_grid =
+        width > 1 && input.lines().all(|line| line.len() == width) && !input.contains([' ', ',']);
+    if !is_grid || input.len() > MAX_LEN {
+        return input;
+    }
+    let mut chars: Vec<char> = input.chars().collect();
+    let mut counts = std::collections::HashMap::new();
+    for &c in chars.iter().filter(|&&c| c != '\n') {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    let Some((&blank, _)) = counts.iter().max_by_key(|&(&c, &count)| (count, c)) else {
+        return input;
+    };
+    if blank.is_alphanumeric() {
+        return input;
+    }
+    for i in 0..chars.len() {
+        if chars[i] == blank || chars[i] == '\n' {
+            continue;
+        }
+        let original = std::mem::replace(&mut chars[i], blank);
+        if !fails(&chars.iter().collect::<String>()) {
+            chars[i] = original;
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinks_lines_fields_and_grids() {
+        let input = "1,2,3\n4 5 6\n7 8 9\n";
+        assert_eq!(shrink(input, |input| input.contains('5')), "5\n");
+        assert_eq!(fields("12 3,,4"), [0..3, 3..6, 6..7]);
+
+        let grid = "....\n.#..\n..#.\n....\n";
+        let two_obstacles = |input: &str| input.matches('#').count() >= 2;
+        assert_eq!(shrink(grid, two_obstacles), ".#..\n..#.\n");
+    }
+
+    #[test]
+    fn reports_shrunk_mismatches() {
+        let dir = std::env::temp_dir().join(format!("crosscheck-{}", std::process::id()));
+        let check = CrossCheck::new(Day::new(11).unwrap())
+            .cases(20)
+            .sizes(1..6)
+            .output_dir(&dir);
+        check.run(|input| Some(input.len()), |input| Some(input.len()));
+
+        let path = check.mismatch_path();
+        let result = catch_unwind(|| {
+            check.run(
+                |input: &str| Some(input.split_whitespace().count().min(2)),
+                |input: &str| Some(input.split_whitespace().count()),
+            )
+        });
+        assert!(result.is_err());
+        let shrunk = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+        assert_eq!(shrunk.split_whitespace().count(), 3);
+        assert!(shrunk.split_whitespace().all(|n| n == "0"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Cycle detection for "simulate until it repeats" puzzles.
+//!
+//! A simulation is described by an initial state and a step function returning the next state,
+//! or [`None`] once the simulation ends on its own (e.g. the guard walks off the grid).
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// How [`detect`] looks for a repeated state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Floyd's tortoise and hare: constant memory, roughly three steps per simulated state.
+    Floyd,
+    /// Brent's algorithm: constant memory, fewer steps than Floyd.
+    #[default]
+    Brent,
+    /// Remembers every state seen: one step per state, but memory grows with the cycle end.
+    Hashing,
+}
+
+/// A cycle found in a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<S> {
+    /// Number of steps before the first state that is part of the cycle.
+    pub start: usize,
+    /// Number of steps after which the cycle repeats.
+    pub length: usize,
+    /// The state the simulation started from.
+    pub initial: S,
+    /// The state after `start` steps, i.e. the first state of the cycle.
+    pub entry: S,
+}
+
+impl<S: Clone> Cycle<S> {
+    /// Maps step `n` to the smallest step with the same state.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// The state after `n` steps, simulating at most `start + length` steps.
+    pub fn state_at<F>(&self, n: usize, mut step: F) -> S
+    where
+        F: FnMut(&S) -> Option<S>,
+    {
+        let (mut state, remaining) = if n < self.start {
+            (self.initial.clone(), n)
+        } else {
+            (self.entry.clone(), (n - self.start) % self.length)
+        };
+        for _ in 0..remaining {
+            state = advance(&mut step, &state);
+        }
+        state
+    }
+}
+
+/// Simulates from `initial` until a state repeats, returning [`None`] if the simulation ends first.
+pub fn detect<S, F>(initial: S, step: F, strategy: Strategy) -> Option<Cycle<S>>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> Option<S>,
+{
+    match strategy {
+        Strategy::Floyd => floyd(initial, step),
+        Strategy::Brent => brent(initial, step),
+        Strategy::Hashing => hashing(initial, step),
+    }
+}
+
+/// The state after `n` steps, skipping whole cycles once one is found.
+///
+/// Returns [`None`] if the simulation ends before step `n`.
+pub fn state_at<S, F>(initial: S, mut step: F, n: usize, strategy: Strategy) -> Option<S>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> Option<S>,
+{
+    match detect(initial.clone(), &mut step, strategy) {
+        Some(cycle) => Some(cycle.state_at(n, step)),
+        None => (0..n).try_fold(initial, |state, _| step(&state)),
+    }
+}
+
+/// Detects a cycle with Floyd's tortoise and hare. Only needs `S: Eq`.
+pub fn floyd<S, F>(initial: S, mut step: F) -> Option<Cycle<S>>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> Option<S>,
+{
+    let mut tortoise = step(&initial)?;
+    let mut hare = step(&tortoise)?;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        let next = step(&hare)?;
+        hare = step(&next)?;
+    }
+
+    // The hare is now a multiple of the cycle length ahead, so they meet again at the entry.
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = advance(&mut step, &tortoise);
+        hare = advance(&mut step, &hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = advance(&mut step, &tortoise);
+    while tortoise != hare {
+        hare = advance(&mut step, &hare);
+        length += 1;
+    }
+
+    Some(Cycle {
+        start,
+        length,
+        initial,
+        entry: tortoise,
+    })
+}
+
+/// Detects a cycle with Brent's algorithm. Only needs `S: Eq`.
+pub fn brent<S, F>(initial: S, mut step: F) -> Option<Cycle<S>>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> Option<S>,
+{
+    // Find the cycle length by teleporting the tortoise to the hare at every power of two.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    // Start the hare `length` steps ahead; both then reach the entry at the same time.
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial.clone();
+    for _ in 0..length {
+        hare = advance(&mut step, &hare);
+    }
+    while tortoise != hare {
+        tortoise = advance(&mut step, &tortoise);
+        hare = advance(&mut step, &hare);
+        start += 1;
+    }
+
+    Some(Cycle {
+        start,
+        length,
+        initial,
+        entry: tortoise,
+    })
+}
+
+/// Detects a cycle by remembering the step at which each state was first seen.
+pub fn hashing<S, F>(initial: S, mut step: F) -> Option<Cycle<S>>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> Option<S>,
+{
+    let mut seen = HashMap::from([(initial.clone(), 0)]);
+    let mut state = initial.clone();
+    for i in 1.. {
+        state = step(&state)?;
+        if let Some(&start) = seen.get(&state) {
+            return Some(Cycle {
+                start,
+                length: i - start,
+                initial,
+                entry: state,
+            });
+        }
+        seen.insert(state.clone(), i);
+    }
+    unreachable!()
+}
+
+/// Steps a state that is known to be part of a cycle, so the simulation cannot end.
+fn advance<S, F>(step: &mut F, state: &S) -> S
+where
+    F: FnMut(&S) -> Option<S>,
+{
+    step(state).expect("step function must be deterministic once a cycle was found")
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: [Strategy; 3] = [Strategy::Floyd, Strategy::Brent, Strategy::Hashing];
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn rho(n: &u32) -> Option<u32> {
+        Some(if *n == 6 { 3 } else { n + 1 })
+    }
+
+    #[test]
+    fn finds_start_and_length() {
+        for strategy in STRATEGIES {
+            let cycle = detect(0, rho, strategy).unwrap();
+            assert_eq!(
+                (cycle.start, cycle.length, cycle.entry),
+                (3, 4, 3),
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extrapolates_state() {
+        for strategy in STRATEGIES {
+            assert_eq!(state_at(0, rho, 2, strategy), Some(2));
+            assert_eq!(state_at(0, rho, 7, strategy), Some(3));
+            assert_eq!(state_at(0, rho, 1_000_000_001, strategy), Some(5));
+        }
+        let cycle = detect(0, rho, Strategy::Brent).unwrap();
+        assert_eq!(cycle.reduce(1_000_000_001), 5);
+    }
+
+    #[test]
+    fn ends_without_cycle() {
+        let stop_at_ten = |n: &u32| (*n < 10).then_some(n + 1);
+        for strategy in STRATEGIES {
+            assert_eq!(detect(0, stop_at_ten, strategy), None);
+            assert_eq!(state_at(0, stop_at_ten, 5, strategy), Some(5));
+            assert_eq!(state_at(0, stop_at_ten, 11, strategy), None);
+        }
+    }
+}
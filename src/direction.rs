@@ -0,0 +1,333 @@
+//! Compass directions for grid puzzles.
+//!
+//! All offsets use the same convention: `(dx, dy)` where `x` grows to the right (east)
+//! and `y` grows downwards (south), i.e. the way a puzzle input reads on screen.
+//! For a `grid::Grid` indexed by `(row, col)`, `dy` is the row delta and `dx` the column delta.
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// One of the four cardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// One of the eight cardinal and intercardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir4 {
+    /// Every direction, clockwise starting from [`Dir4::North`].
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    /// Iterates over every direction, clockwise starting from [`Dir4::North`].
+    pub fn iter() -> impl Iterator<Item = Dir4> {
+        Self::ALL.into_iter()
+    }
+
+    /// The `(dx, dy)` vector of a single step in this direction.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Dir4::North => (0, -1),
+            Dir4::East => (1, 0),
+            Dir4::South => (0, 1),
+            Dir4::West => (-1, 0),
+        }
+    }
+
+    /// The direction after a quarter turn to the right.
+    pub const fn turn_cw(self) -> Dir4 {
+        match self {
+            Dir4::North => Dir4::East,
+            Dir4::East => Dir4::South,
+            Dir4::South => Dir4::West,
+            Dir4::West => Dir4::North,
+        }
+    }
+
+    /// The direction after a quarter turn to the left.
+    pub const fn turn_ccw(self) -> Dir4 {
+        match self {
+            Dir4::North => Dir4::West,
+            Dir4::East => Dir4::North,
+            Dir4::South => Dir4::East,
+            Dir4::West => Dir4::South,
+        }
+    }
+
+    /// The direction pointing the other way.
+    pub const fn opposite(self) -> Dir4 {
+        match self {
+            Dir4::North => Dir4::South,
+            Dir4::East => Dir4::West,
+            Dir4::South => Dir4::North,
+            Dir4::West => Dir4::East,
+        }
+    }
+
+    /// Moves `(x, y)` by `distance` steps in this direction.
+    pub const fn apply(self, (x, y): (isize, isize), distance: isize) -> (isize, isize) {
+        let (dx, dy) = self.offset();
+        (x + dx * distance, y + dy * distance)
+    }
+
+    /// Moves a `(row, col)` grid index one step, returning [`None`] if it leaves a `rows` by `cols` grid.
+    pub fn step(
+        self,
+        (row, col): (usize, usize),
+        rows: usize,
+        cols: usize,
+    ) -> Option<(usize, usize)> {
+        let (dx, dy) = self.offset();
+        let row = row.checked_add_signed(dy).filter(|&r| r < rows)?;
+        let col = col.checked_add_signed(dx).filter(|&c| c < cols)?;
+        Some((row, col))
+    }
+
+    /// The arrow character used by puzzle inputs for this direction.
+    pub const fn arrow(self) -> char {
+        match self {
+            Dir4::North => '^',
+            Dir4::East => '>',
+            Dir4::South => 'v',
+            Dir4::West => '<',
+        }
+    }
+}
+
+impl Dir8 {
+    /// Every direction, clockwise starting from [`Dir8::North`].
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    /// The four diagonal directions, clockwise starting from [`Dir8::NorthEast`].
+    pub const DIAGONALS: [Dir8; 4] = [
+        Dir8::NorthEast,
+        Dir8::SouthEast,
+        Dir8::SouthWest,
+        Dir8::NorthWest,
+    ];
+
+    /// Iterates over every direction, clockwise starting from [`Dir8::North`].
+    pub fn iter() -> impl Iterator<Item = Dir8> {
+        Self::ALL.into_iter()
+    }
+
+    /// The `(dx, dy)` vector of a single step in this direction.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Dir8::North => (0, -1),
+            Dir8::NorthEast => (1, -1),
+            Dir8::East => (1, 0),
+            Dir8::SouthEast => (1, 1),
+            Dir8::South => (0, 1),
+            Dir8::SouthWest => (-1, 1),
+            Dir8::West => (-1, 0),
+            Dir8::NorthWest => (-1, -1),
+        }
+    }
+
+    /// The direction after an eighth of a turn to the right.
+    pub const fn turn_cw(self) -> Dir8 {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// The direction after an eighth of a turn to the left.
+    pub const fn turn_ccw(self) -> Dir8 {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    /// The direction pointing the other way.
+    pub const fn opposite(self) -> Dir8 {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// Whether this is one of the four diagonal directions.
+    pub const fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Moves `(x, y)` by `distance` steps in this direction.
+    pub const fn apply(self, (x, y): (isize, isize), distance: isize) -> (isize, isize) {
+        let (dx, dy) = self.offset();
+        (x + dx * distance, y + dy * distance)
+    }
+
+    /// Moves a `(row, col)` grid index one step, returning [`None`] if it leaves a `rows` by `cols` grid.
+    pub fn step(
+        self,
+        (row, col): (usize, usize),
+        rows: usize,
+        cols: usize,
+    ) -> Option<(usize, usize)> {
+        let (dx, dy) = self.offset();
+        let row = row.checked_add_signed(dy).filter(|&r| r < rows)?;
+        let col = col.checked_add_signed(dx).filter(|&c| c < cols)?;
+        Some((row, col))
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(value: Dir4) -> Self {
+        match value {
+            Dir4::North => Dir8::North,
+            Dir4::East => Dir8::East,
+            Dir4::South => Dir8::South,
+            Dir4::West => Dir8::West,
+        }
+    }
+}
+
+impl TryFrom<Dir8> for Dir4 {
+    type Error = DirParseError;
+
+    fn try_from(value: Dir8) -> Result<Self, Self::Error> {
+        match value {
+            Dir8::North => Ok(Dir4::North),
+            Dir8::East => Ok(Dir4::East),
+            Dir8::South => Ok(Dir4::South),
+            Dir8::West => Ok(Dir4::West),
+            _ => Err(DirParseError),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl TryFrom<char> for Dir4 {
+    type Error = DirParseError;
+
+    /// Parses an arrow (`^>v<`) or a compass letter (`NESW`, either case).
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '^' | 'N' | 'n' => Ok(Dir4::North),
+            '>' | 'E' | 'e' => Ok(Dir4::East),
+            'v' | 'V' | 'S' | 's' => Ok(Dir4::South),
+            '<' | 'W' | 'w' => Ok(Dir4::West),
+            _ => Err(DirParseError),
+        }
+    }
+}
+
+impl TryFrom<char> for Dir8 {
+    type Error = DirParseError;
+
+    /// Parses an arrow (`^>v<`) or a compass letter (`NESW`, either case).
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Dir4::try_from(value).map(Dir8::from)
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = DirParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Dir4::try_from(c),
+            _ => Err(DirParseError),
+        }
+    }
+}
+
+impl FromStr for Dir8 {
+    type Err = DirParseError;
+
+    /// Parses a single arrow or compass letter, or a two letter intercardinal like `NE` or `sw`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "NE" => Ok(Dir8::NorthEast),
+            "SE" => Ok(Dir8::SouthEast),
+            "SW" => Ok(Dir8::SouthWest),
+            "NW" => Ok(Dir8::NorthWest),
+            _ => Dir4::from_str(s).map(Dir8::from),
+        }
+    }
+}
+
+impl Display for Dir4 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Display for Dir8 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// An error which can be returned when parsing a [`Dir4`] or [`Dir8`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct DirParseError;
+
+impl Error for DirParseError {}
+
+impl Display for DirParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("expecting one of `^>v<`, `NESW` or an intercardinal like `NE`")
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{Dir4, Dir8};
+
+    #[test]
+    fn turns_are_inverse() {
+        for dir in Dir4::iter() {
+            assert_eq!(dir.turn_cw().turn_ccw(), dir);
+            assert_eq!(dir.turn_cw().turn_cw(), dir.opposite());
+        }
+        for dir in Dir8::iter() {
+            assert_eq!(dir.turn_cw().turn_ccw(), dir);
+            assert_eq!(dir.turn_cw().turn_cw().turn_cw().turn_cw(), dir.opposite());
+        }
+    }
+
+    #[test]
+    fn offsets_agree() {
+        for dir in Dir4::iter() {
+            assert_eq!(Dir8::from(dir).offset(), dir.offset());
+            let (dx, dy) = dir.offset();
+            assert_eq!(dir.opposite().offset(), (-dx, -dy));
+        }
+        assert_eq!(Dir8::NorthEast.offset(), (1, -1));
+        assert_eq!(Dir4::North.step((0, 3), 5, 5), None);
+        assert_eq!(Dir4::East.step((0, 3), 5, 5), Some((0, 4)));
+    }
+
+    #[test]
+    fn parses_arrows_and_letters() {
+        assert_eq!(Dir4::try_from('^'), Ok(Dir4::North));
+        assert_eq!(Dir4::try_from('v'), Ok(Dir4::South));
+        assert_eq!(Dir4::try_from('W'), Ok(Dir4::West));
+        assert!(Dir4::try_from('x').is_err());
+        assert_eq!("nw".parse::<Dir8>(), Ok(Dir8::NorthWest));
+        assert_eq!(">".parse::<Dir8>(), Ok(Dir8::East));
+        assert_eq!(Dir4::iter().map(Dir4::arrow).collect::<String>(), "^>v<");
+    }
+}
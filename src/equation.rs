@@ -0,0 +1,205 @@
+//! Solver for "insert operators between these numbers to reach the target" puzzles.
+//!
+//! Operators are evaluated strictly left to right, so the solver works backwards from the target:
+//! the last operator must turn some value into the target using the last operand, and inverting
+//! it either gives that value or proves the operator impossible. Most branches die immediately
+//! (the target is not divisible, does not end in the right digits, ...), which is much cheaper
+//! than trying every combination forwards.
+use std::fmt::Debug;
+
+use crate::num::{concat, digit_count, split_at_digit};
+
+/// An operator with an inverse, usable by [`Solver`].
+pub trait Operator: Copy + Debug {
+    /// `a op b`, or [`None`] if it overflows.
+    fn apply(self, a: u64, b: u64) -> Option<u64>;
+
+    /// The `a` for which `a op b == result`, or [`None`] if there is none.
+    fn invert(self, result: u64, b: u64) -> Option<u64>;
+
+    /// How the operator is written in an expression.
+    fn symbol(self) -> &'static str;
+}
+
+/// The operators that come up in the puzzles.
+///
+/// Operands are expected to be positive: a right operand of zero makes multiplication
+/// impossible to invert, so such branches are never matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Add,
+    Multiply,
+    /// Joins the digits of both operands, e.g. `12 || 345 == 12345`.
+    Concatenate,
+}
+
+impl Op {
+    pub const ADD_MUL: [Op; 2] = [Op::Add, Op::Multiply];
+    pub const ALL: [Op; 3] = [Op::Add, Op::Multiply, Op::Concatenate];
+}
+
+impl Operator for Op {
+    fn apply(self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Op::Add => a.checked_add(b),
+            Op::Multiply => a.checked_mul(b),
+            Op::Concatenate => concat(a, b),
+        }
+    }
+
+    fn invert(self, result: u64, b: u64) -> Option<u64> {
+        match self {
+            Op::Add => result.checked_sub(b),
+            Op::Multiply => (b != 0 && result.is_multiple_of(b)).then(|| result / b),
+            Op::Concatenate => {
+                let (rest, tail) = split_at_digit(result, digit_count(b));
+                (tail == b).then_some(rest)
+            }
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Multiply => "*",
+            Op::Concatenate => "||",
+        }
+    }
+}
+
+/// Evaluates `operands` joined by `operators` from left to right, or [`None`] if it overflows.
+pub fn evaluate<O: Operator>(operands: &[u64], operators: &[O]) -> Option<u64> {
+    let (&first, rest) = operands.split_first()?;
+    rest.iter()
+        .zip(operators)
+        .try_fold(first, |acc, (&b, op)| op.apply(acc, b))
+}
+
+/// Writes `operands` joined by `operators`, e.g. `81 + 40 * 27`.
+pub fn expression<O: Operator>(operands: &[u64], operators: &[O]) -> String {
+    let mut out = operands.first().map(u64::to_string).unwrap_or_default();
+    for (b, op) in operands.iter().skip(1).zip(operators) {
+        out.push_str(&format!(" {} {b}", op.symbol()));
+    }
+    out
+}
+
+/// Searches for operators between operands that make them evaluate to a target.
+///
+/// ```
+/// # use advent_of_code::equation::{expression, Op, Solver};
+/// let solver = Solver::new(&Op::ALL);
+/// let operands = [6, 8, 6, 15];
+/// let operators = solver.solve(7290, &operands).unwrap();
+/// assert_eq!(expression(&operands, &operators), "6 * 8 || 6 * 15");
+/// assert!(!Solver::new(&Op::ADD_MUL).is_solvable(7290, &operands));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Solver<'a, O> {
+    operators: &'a [O],
+}
+
+impl<'a, O: Operator> Solver<'a, O> {
+    /// Creates a solver that may put any of `operators` between two operands.
+    pub fn new(operators: &'a [O]) -> Self {
+        Self { operators }
+    }
+
+    /// Whether any operators make `operands` evaluate to `target`.
+    pub fn is_solvable(&self, target: u64, operands: &[u64]) -> bool {
+        self.solve(target, operands).is_some()
+    }
+
+    /// The first operators found that make `operands` evaluate to `target`, trying operators in
+    /// the order the solver was created with, starting from the last position.
+    pub fn solve(&self, target: u64, operands: &[u64]) -> Option<Vec<O>> {
+        let mut found = None;
+        self.search(target, operands, &mut vec![], &mut |ops| {
+            found = Some(ops);
+            false
+        });
+        found
+    }
+
+    /// Every sequence of operators that makes `operands` evaluate to `target`.
+    pub fn solve_all(&self, target: u64, operands: &[u64]) -> Vec<Vec<O>> {
+        let mut found = vec![];
+        self.search(target, operands, &mut vec![], &mut |ops| {
+            found.push(ops);
+            true
+        });
+        found
+    }
+
+    /// Searches backwards from `target`, with the operators after `operands` already chosen in
+    /// reverse order in `suffix`. Calls `on_found` for every solution until it returns `false`,
+    /// and returns whether the search should go on.
+    fn search(
+        &self,
+        target: u64,
+        operands: &[u64],
+        suffix: &mut Vec<O>,
+        on_found: &mut impl FnMut(Vec<O>) -> bool,
+    ) -> bool {
+        let Some((&last, rest)) = operands.split_last() else {
+            return true;
+        };
+        if rest.is_empty() {
+            if last == target {
+                return on_found(suffix.iter().rev().copied().collect());
+            }
+            return true;
+        }
+        for &op in self.operators {
+            let Some(previous) = op.invert(target, last) else {
+                continue;
+            };
+            suffix.push(op);
+            let go_on = self.search(previous, rest, suffix, on_found);
+            suffix.pop();
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverts_operators() {
+        for op in Op::ALL {
+            for (a, b) in [(12, 345), (1, 1), (0, 7), (99, 10)] {
+                let result = op.apply(a, b).unwrap();
+                assert_eq!(op.invert(result, b), Some(a), "{a} {op:?} {b}");
+            }
+        }
+        assert_eq!(Op::Concatenate.invert(12345, 45), Some(123));
+        assert_eq!(Op::Concatenate.invert(12345, 5), Some(1234));
+        assert_eq!(Op::Concatenate.invert(12345, 44), None);
+        assert_eq!(Op::Multiply.invert(10, 3), None);
+        assert_eq!(Op::Add.apply(u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn finds_all_solutions() {
+        let solver = Solver::new(&Op::ADD_MUL);
+        let operands = [81, 40, 27];
+        let mut all = solver.solve_all(3267, &operands);
+        all.sort_by_key(|ops| expression(&operands, ops));
+        assert_eq!(
+            all,
+            [vec![Op::Multiply, Op::Add], vec![Op::Add, Op::Multiply]]
+        );
+        for ops in &all {
+            assert_eq!(evaluate(&operands, ops), Some(3267));
+        }
+        assert!(solver.solve_all(1, &operands).is_empty());
+        assert_eq!(solver.solve(5, &[5]), Some(vec![]));
+    }
+}
@@ -0,0 +1,383 @@
+//! Random puzzle inputs of any size, for stress tests and benchmarks beyond the real inputs.
+//!
+//! Every generator takes a size (lines, grid side, digits, ... depending on the day) and a seeded
+//! [`Rng`], so the same seed always gives the same input. Run them with
+//! `cargo gen <day> --size <n> --seed <s>`.
+use std::ops::Range;
+
+use crate::direction::Dir4;
+use crate::equation::{evaluate, Op};
+use crate::template::Day;
+
+/// A small, fast, seedable random number generator (SplitMix64). Not for cryptography.
+///
+/// ```
+/// # use advent_of_code::generate::Rng;
+/// let mut rng = Rng::new(42);
+/// let roll = rng.range(1..7);
+/// assert!((1..7).contains(&roll));
+/// assert_eq!(Rng::new(42).range(1..7), roll);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    /// An index below `len`, which must not be 0.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    /// Shuffles `items` in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Makes an input for one day.
+#[derive(Debug, Clone, Copy)]
+pub struct Generator {
+    /// What the size means, e.g. "lines" or "rows and columns".
+    pub unit: &'static str,
+    /// The size of a typical real input.
+    pub default_size: usize,
+    generate: fn(usize, &mut Rng) -> String,
+}
+
+impl Generator {
+    pub fn generate(&self, size: usize, seed: u64) -> String {
+        (self.generate)(size, &mut Rng::new(seed))
+    }
+}
+
+/// The generator for `day`, if there is one.
+pub fn generator(day: Day) -> Option<Generator> {
+    let (unit, default_size, generate): (_, _, fn(usize, &mut Rng) -> String) =
+        match day.into_inner() {
+            1 => ("lines", 1000, location_lists),
+            2 => ("reports", 1000, reports),
+            3 => ("characters", 20_000, corrupted_memory),
+            4 => ("rows and columns", 140, word_search),
+            5 => ("updates", 200, print_queue),
+            6 => ("rows and columns", 130, guard_map),
+            7 => ("equations", 850, equations),
+            8 => ("rows and columns", 50, antenna_map),
+            9 => ("digits", 20_000, disk_map),
+            10 => ("rows and columns", 50, topographic_map),
+            11 => ("stones", 8, stones),
+            _ => return None,
+        };
+    Some(Generator {
+        unit,
+        default_size,
+        generate,
+    })
+}
+
+fn grid_to_string(grid: &[Vec<char>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+/// Day 1: two columns of five-digit location ids, with some ids repeated.
+fn location_lists(lines: usize, rng: &mut Rng) -> String {
+    let ids: Vec<u64> = (0..lines.max(1))
+        .map(|_| rng.range(10_000..100_000))
+        .collect();
+    (0..lines)
+        .map(|_| {
+            let right = if rng.chance(0.3) {
+                *rng.pick(&ids)
+            } else {
+                rng.range(10_000..100_000)
+            };
+            format!("{}   {right}\n", rng.pick(&ids))
+        })
+        .collect()
+}
+
+/// Day 2: reports that mostly change steadily, some with one or two bad levels.
+fn reports(reports: usize, rng: &mut Rng) -> String {
+    (0..reports)
+        .map(|_| {
+            let len = rng.range(5..9) as usize;
+            let increasing = rng.chance(0.5);
+            let mut level = rng.range(20..70) as i64;
+            let mut levels = vec![];
+            for _ in 0..len {
+                levels.push(level);
+                let step = if rng.chance(0.05) {
+                    rng.range(0..8) as i64
+                } else {
+                    rng.range(1..4) as i64
+                };
+                level += if increasing { step } else { -step };
+            }
+            if rng.chance(0.2) {
+                let i = rng.index(len);
+                levels[i] += rng.range(1..5) as i64 - 2;
+            }
+            let levels: Vec<String> = levels.iter().map(|l| l.max(&1).to_string()).collect();
+            levels.join(" ") + "\n"
+        })
+        .collect()
+}
+
+/// Day 3: noise with `mul`, `do` and `don't` instructions, and broken ones, mixed in.
+fn corrupted_memory(characters: usize, rng: &mut Rng) -> String {
+    const NOISE: &[u8] = b"!@#$%^&*()[]{}<>,;:'?+-_=/ selectwhomul";
+    let mut memory = String::new();
+    while memory.len() < characters {
+        match rng.range(0..10) {
+            0..=1 => {
+                let (a, b) = (rng.range(1..1000), rng.range(1..1000));
+                memory += &format!("mul({a},{b})");
+            }
+            2 => memory += *rng.pick(&["do()", "don't()", "mul(4*", "mul ( 2 , 4 )", "mul(6,9!"]),
+            _ => {
+                for _ in 0..rng.range(1..8) {
+                    memory.push(*rng.pick(NOISE) as char);
+                }
+            }
+        }
+        if rng.chance(0.002) {
+            memory.push('\n');
+        }
+    }
+    memory + "\n"
+}
+
+/// Day 4: a square of the letters in "XMAS".
+fn word_search(side: usize, rng: &mut Rng) -> String {
+    let grid: Vec<Vec<char>> = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| *rng.pick(&['X', 'M', 'A', 'S']))
+                .collect()
+        })
+        .collect();
+    grid_to_string(&grid)
+}
+
+/// Day 5: rules for every pair of pages that follow a hidden order, and updates in random or
+/// correct order.
+fn print_queue(updates: usize, rng: &mut Rng) -> String {
+    let mut pages: Vec<u64> = (10..100).collect();
+    rng.shuffle(&mut pages);
+    let mut input = String::new();
+    for (i, before) in pages.iter().enumerate() {
+        for after in &pages[i + 1..] {
+            input += &format!("{before}|{after}\n");
+        }
+    }
+    input.push('\n');
+    for _ in 0..updates {
+        let len = 2 * rng.range(2..12) as usize + 1;
+        let mut update = pages.clone();
+        rng.shuffle(&mut update);
+        update.truncate(len);
+        if rng.chance(0.5) {
+            update.sort_by_key(|page| pages.iter().position(|p| p == page));
+        }
+        let update: Vec<String> = update.iter().map(u64::to_string).collect();
+        input += &(update.join(",") + "\n");
+    }
+    input
+}
+
+/// The number of steps a guard starting at `start` facing north takes to walk off `grid`, or
+/// [`None`] if it loops.
+fn guard_escapes(grid: &[Vec<char>], start: (usize, usize)) -> Option<usize> {
+    let (rows, cols) = (grid.len(), grid[0].len());
+    let mut seen = vec![[false; 4]; rows * cols];
+    let (mut position, mut direction) = (start, Dir4::North);
+    for steps in 0.. {
+        let visited = &mut seen[position.0 * cols + position.1][direction as usize];
+        if *visited {
+            return None;
+        }
+        *visited = true;
+        match direction.step(position, rows, cols) {
+            None => return Some(steps),
+            Some((row, col)) if grid[row][col] == '#' => direction = direction.turn_cw(),
+            Some(next) => position = next,
+        }
+    }
+    unreachable!()
+}
+
+/// Day 6: scattered obstacles and a guard who leaves the map after a walk at least as long as
+/// its side.
+fn guard_map(side: usize, rng: &mut Rng) -> String {
+    let side = side.max(2);
+    loop {
+        let mut grid: Vec<Vec<char>> = (0..side)
+            .map(|_| {
+                (0..side)
+                    .map(|_| if rng.chance(0.08) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        let start = (rng.index(side), rng.index(side));
+        grid[start.0][start.1] = '^';
+        if guard_escapes(&grid, start).is_some_and(|steps| steps >= side) {
+            return grid_to_string(&grid);
+        }
+    }
+}
+
+/// Day 7: equations where about half the results can be made from the operands.
+fn equations(equations: usize, rng: &mut Rng) -> String {
+    (0..equations)
+        .map(|_| loop {
+            let operands: Vec<u64> = (0..rng.range(2..9)).map(|_| rng.range(1..100)).collect();
+            let operators: Vec<Op> = (1..operands.len()).map(|_| *rng.pick(&Op::ALL)).collect();
+            let Some(mut result) = evaluate(&operands, &operators) else {
+                continue;
+            };
+            if rng.chance(0.5) {
+                result += 1;
+            }
+            let operands: Vec<String> = operands.iter().map(u64::to_string).collect();
+            break format!("{result}: {}\n", operands.join(" "));
+        })
+        .collect()
+}
+
+/// Day 8: antennas of up to 62 frequencies, about one in 25 cells.
+fn antenna_map(side: usize, rng: &mut Rng) -> String {
+    let frequencies: Vec<char> = ('0'..='9').chain('a'..='z').chain('A'..='Z').collect();
+    let used = &frequencies[..rng.range(1..frequencies.len() as u64 + 1) as usize];
+    let grid: Vec<Vec<char>> = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| {
+                    if rng.chance(0.04) {
+                        *rng.pick(used)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    grid_to_string(&grid)
+}
+
+/// Day 9: a disk map alternating files (1 to 9 blocks) and free space (0 to 9 blocks), ending
+/// with a file.
+fn disk_map(digits: usize, rng: &mut Rng) -> String {
+    let digits = digits.max(1) | 1;
+    let map: String = (0..digits)
+        .map(|i| {
+            let blocks = if i % 2 == 0 {
+                rng.range(1..10)
+            } else {
+                rng.range(0..10)
+            };
+            char::from_digit(blocks as u32, 10).unwrap()
+        })
+        .collect();
+    map + "\n"
+}
+
+/// Day 10: random heights with trails from 0 to 9 carved into them.
+fn topographic_map(side: usize, rng: &mut Rng) -> String {
+    let side = side.max(1);
+    let mut grid: Vec<Vec<char>> = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| char::from_digit(rng.range(0..10) as u32, 10).unwrap())
+                .collect()
+        })
+        .collect();
+    for _ in 0..side * side / 20 {
+        let mut trail = vec![(rng.index(side), rng.index(side))];
+        while trail.len() < 10 {
+            let last = *trail.last().unwrap();
+            let next: Vec<_> = Dir4::iter()
+                .filter_map(|d| d.step(last, side, side))
+                .filter(|next| !trail.contains(next))
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+            trail.push(*rng.pick(&next));
+        }
+        for (height, (row, col)) in trail.into_iter().enumerate() {
+            grid[row][col] = char::from_digit(height as u32, 10).unwrap();
+        }
+    }
+    grid_to_string(&grid)
+}
+
+/// Day 11: stones with up to six digits.
+fn stones(stones: usize, rng: &mut Rng) -> String {
+    let stones: Vec<String> = (0..stones)
+        .map(|_| rng.range(0..1_000_000).to_string())
+        .collect();
+    stones.join(" ") + "\n"
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_the_same_input_for_a_seed() {
+        for day in 1..=11 {
+            let generator = generator(Day::new(day).unwrap()).unwrap();
+            let input = generator.generate(30, 7);
+            assert!(!input.is_empty(), "day {day}");
+            assert_eq!(input, generator.generate(30, 7), "day {day}");
+        }
+        assert!(generator(Day::new(12).unwrap()).is_none());
+    }
+
+    #[test]
+    fn sizes_inputs() {
+        let day = |day| generator(Day::new(day).unwrap()).unwrap();
+        assert_eq!(day(9).generate(1001, 1).trim().len(), 1001);
+        assert_eq!(day(6).generate(12, 1).lines().count(), 12);
+        assert_eq!(day(6).generate(12, 1).matches('^').count(), 1);
+        assert_eq!(day(1).generate(5, 1).lines().count(), 5);
+        let equations = day(7).generate(20, 3);
+        assert!(equations
+            .lines()
+            .all(|line| line.split_once(": ").is_some()));
+    }
+}
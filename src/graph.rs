@@ -0,0 +1,218 @@
+//! Graph algorithms over adjacency maps like day 5's `HashMap<u32, Vec<u32>>`,
+//! where `rules[&a]` lists every node that must come after `a`.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// Sorts every node mentioned in `rules` so that each node comes before all of its successors.
+///
+/// Uses Kahn's algorithm, always placing the smallest ready node, so the result is deterministic.
+/// Returns a [`CycleError`] naming one offending cycle if the rules cannot be satisfied.
+pub fn topological_sort<N>(rules: &HashMap<N, Vec<N>>) -> Result<Vec<N>, CycleError<N>>
+where
+    N: Copy + Eq + Hash + Ord,
+{
+    let mut nodes: Vec<N> = rules
+        .iter()
+        .flat_map(|(node, afters)| std::iter::once(node).chain(afters))
+        .copied()
+        .collect::<HashSet<N>>()
+        .into_iter()
+        .collect();
+    nodes.sort_unstable();
+    kahn(&nodes, rules)
+}
+
+/// Sorts `nodes` using only the rules where both sides are part of `nodes`.
+///
+/// This is what day 5 needs: the full rule set may be cyclic, but the rules that apply to a single
+/// update are not. Whenever several nodes could go next, the one earliest in `nodes` is placed, so
+/// `nodes` comes back unchanged if it already satisfies the rules. Duplicate nodes are kept
+/// together at the position of the first occurrence.
+pub fn topological_sort_subset<N>(
+    rules: &HashMap<N, Vec<N>>,
+    nodes: &[N],
+) -> Result<Vec<N>, CycleError<N>>
+where
+    N: Copy + Eq + Hash,
+{
+    let mut seen = HashSet::with_capacity(nodes.len());
+    let unique: Vec<N> = nodes.iter().copied().filter(|n| seen.insert(*n)).collect();
+    let sorted = kahn(&unique, rules)?;
+
+    let mut counts: HashMap<N, usize> = HashMap::with_capacity(unique.len());
+    for node in nodes {
+        *counts.entry(*node).or_default() += 1;
+    }
+    Ok(sorted
+        .into_iter()
+        .flat_map(|node| std::iter::repeat_n(node, counts[&node]))
+        .collect())
+}
+
+/// Kahn's algorithm restricted to `nodes`, which must not contain duplicates. Ready nodes are
+/// placed in the order they appear in `nodes`.
+fn kahn<N>(nodes: &[N], rules: &HashMap<N, Vec<N>>) -> Result<Vec<N>, CycleError<N>>
+where
+    N: Copy + Eq + Hash,
+{
+    let index: HashMap<N, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let mut successors: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+    let mut in_degree = vec![0usize; nodes.len()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        let Some(afters) = rules.get(node) else {
+            continue;
+        };
+        for after in afters {
+            if let Some(&j) = index.get(after) {
+                successors[i].push(j);
+                in_degree[j] += 1;
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..nodes.len())
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut sorted = Vec::with_capacity(nodes.len());
+
+    while let Some(Reverse(i)) = ready.pop() {
+        sorted.push(nodes[i]);
+        for &j in &successors[i] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if sorted.len() == nodes.len() {
+        Ok(sorted)
+    } else {
+        Err(CycleError {
+            cycle: find_cycle(&successors, &in_degree)
+                .into_iter()
+                .map(|i| nodes[i])
+                .collect(),
+        })
+    }
+}
+
+/// Finds one cycle among the nodes Kahn's algorithm could not place (those with `in_degree > 0`).
+///
+/// Every such node has a predecessor that was not placed either, so walking predecessors
+/// must eventually revisit a node.
+fn find_cycle(successors: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+    let mut predecessor = vec![None; successors.len()];
+    for (i, afters) in successors.iter().enumerate() {
+        if in_degree[i] == 0 {
+            continue;
+        }
+        for &j in afters {
+            if in_degree[j] > 0 {
+                predecessor[j] = Some(i);
+            }
+        }
+    }
+
+    let start = in_degree.iter().position(|&d| d > 0).unwrap();
+    let mut position_in_walk = HashMap::new();
+    let mut walk = vec![];
+    let mut current = start;
+    while !position_in_walk.contains_key(&current) {
+        position_in_walk.insert(current, walk.len());
+        walk.push(current);
+        current = predecessor[current].unwrap();
+    }
+
+    // The walk follows edges backwards, so flip it to read in rule order.
+    let mut cycle = walk.split_off(position_in_walk[&current]);
+    cycle.reverse();
+    cycle
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error returned when the rules contain a cycle, so no order can satisfy all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<N> {
+    /// The nodes of one cycle in rule order: each must come before the next, and the last before the first.
+    pub cycle: Vec<N>,
+}
+
+impl<N: Debug + Display> Error for CycleError<N> {}
+
+impl<N: Display> Display for CycleError<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ordering rules contain a cycle: ")?;
+        for node in &self.cycle {
+            write!(f, "{node} -> ")?;
+        }
+        match self.cycle.first() {
+            Some(first) => write!(f, "{first}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pairs: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+        pairs.iter().fold(HashMap::new(), |mut map, &(a, b)| {
+            map.entry(a).or_insert(vec![]).push(b);
+            map
+        })
+    }
+
+    #[test]
+    fn sorts_all_nodes() {
+        let rules = rules(&[(3, 1), (1, 2), (3, 2), (4, 1)]);
+        assert_eq!(topological_sort(&rules), Ok(vec![3, 4, 1, 2]));
+    }
+
+    #[test]
+    fn reports_cycle() {
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (0, 1)]);
+        let err = topological_sort(&rules).unwrap_err();
+        assert_eq!(err.cycle.len(), 3);
+        let start = err.cycle.iter().position(|&n| n == 1).unwrap();
+        let rotated = [&err.cycle[start..], &err.cycle[..start]].concat();
+        assert_eq!(rotated, vec![1, 2, 3]);
+        assert!(err.to_string().contains("->"));
+    }
+
+    #[test]
+    fn sorts_subset_ignoring_other_rules() {
+        // The full rule set is cyclic, but not once restricted to the update.
+        let rules = rules(&[(1, 2), (2, 3), (3, 1)]);
+        assert!(topological_sort(&rules).is_err());
+        assert_eq!(topological_sort_subset(&rules, &[2, 1]), Ok(vec![1, 2]));
+        assert_eq!(
+            topological_sort_subset(&rules, &[5, 3, 2]),
+            Ok(vec![5, 2, 3])
+        );
+    }
+
+    #[test]
+    fn keeps_input_order_where_rules_allow() {
+        // Only 2 and 3 are ready at first, and 2 comes first in the input. An update that
+        // already follows the rules is left as it is.
+        let rules = rules(&[(3, 1), (1, 4)]);
+        assert_eq!(
+            topological_sort_subset(&rules, &[1, 4, 2, 3]),
+            Ok(vec![2, 3, 1, 4])
+        );
+        assert_eq!(
+            topological_sort_subset(&rules, &[3, 1, 4, 2]),
+            Ok(vec![3, 1, 4, 2])
+        );
+    }
+}
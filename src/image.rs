@@ -0,0 +1,341 @@
+//! Export of grids as PPM or PNG images, for grids too large to read in a terminal.
+//!
+//! Solutions check [`render_requested`] (set by `cargo solve <day> --render`) and write their
+//! images to [`render_path`]. Simulations can write one numbered image per step with
+//! [`ImageSequence`].
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use grid::Grid;
+
+use crate::recorder::VISUALIZATIONS_DIR;
+use crate::render::{Cell, Color, GridRenderer};
+use crate::template::{flag, Day};
+
+/// Whether the solution was started with `cargo solve <day> --render`.
+pub fn render_requested() -> bool {
+    flag("--render")
+}
+
+/// The image `--render` writes for one part of a day, e.g. `data/visualizations/06-1.png`.
+pub fn render_path(day: Day, part: u8) -> PathBuf {
+    Path::new(VISUALIZATIONS_DIR).join(format!("{day}-{part}.png"))
+}
+
+/// A pixel color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GRAY: Rgb = Rgb(128, 128, 128);
+
+    /// Blends linearly from `self` at `t = 0.0` to `other` at `t = 1.0`.
+    pub fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+
+    /// Halves the brightness, for dimmed cells.
+    pub fn dim(self) -> Rgb {
+        Rgb(self.0 / 2, self.1 / 2, self.2 / 2)
+    }
+}
+
+impl From<Color> for Rgb {
+    fn from(color: Color) -> Rgb {
+        match color {
+            Color::Red => Rgb(220, 50, 47),
+            Color::Green => Rgb(80, 200, 70),
+            Color::Yellow => Rgb(240, 200, 40),
+            Color::Blue => Rgb(50, 110, 230),
+            Color::Magenta => Rgb(200, 60, 200),
+            Color::Cyan => Rgb(40, 200, 210),
+        }
+    }
+}
+
+/// The color of a rendered cell: its background, else its foreground, else gray for
+/// anything that is not empty space (`.` or ` `).
+fn cell_color(cell: &Cell) -> Rgb {
+    let color = match (cell.bg, cell.fg, cell.ch) {
+        (Some(bg), _, _) => bg.into(),
+        (None, Some(fg), _) => fg.into(),
+        (None, None, '.' | ' ') => Rgb::BLACK,
+        (None, None, _) => Rgb::GRAY,
+    };
+    if cell.dim {
+        color.dim()
+    } else {
+        color
+    }
+}
+
+/// Image format, picked from the file extension by [`Image::save`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Binary portable pixmap (`P6`), readable by most image viewers and trivial to write.
+    Ppm,
+    Png,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("ppm") => Format::Ppm,
+            _ => Format::Png,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+        }
+    }
+}
+
+/// An RGB image, drawn with one square of `scale` by `scale` pixels per grid cell.
+///
+/// ```
+/// # use advent_of_code::image::{Image, Rgb};
+/// # use grid::grid;
+/// let heights = grid![[0, 9][9, 0]];
+/// let image = Image::from_grid(&heights, 2, |&h| Rgb::BLACK.lerp(Rgb::WHITE, h as f64 / 9.0));
+/// assert_eq!((image.width(), image.height()), (4, 4));
+/// assert_eq!(image.pixel(2, 1), Rgb::WHITE);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Draws `grid`, turning each value into a color with `f`.
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, f: impl Fn(&T) -> Rgb) -> Self {
+        let scale = scale.max(1);
+        let (width, height) = (grid.cols() * scale, grid.rows() * scale);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in grid.iter_rows() {
+            let line: Vec<Rgb> = row
+                .flat_map(|value| std::iter::repeat_n(f(value), scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Draws the cells of a renderer, using the colors of its layers.
+    pub fn from_renderer(view: &GridRenderer, scale: usize) -> Self {
+        let cells = Grid::from_vec(
+            (0..view.rows())
+                .flat_map(|row| (0..view.cols()).map(move |col| (row, col)))
+                .map(|position| cell_color(view.cell(position).unwrap()))
+                .collect(),
+            view.cols(),
+        );
+        Self::from_grid(&cells, scale, |&color| color)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The color of the pixel at `(x, y)`, counted from the top left.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&Rgb(r, g, b)| [r, g, b])
+            .collect()
+    }
+
+    /// Encodes the image as a binary PPM (`P6`) file.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.bytes());
+        out
+    }
+
+    /// Encodes the image as a PNG file.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write_png(&mut out)
+            .expect("writing a PNG to memory cannot fail");
+        out
+    }
+
+    fn write_png(&self, writer: impl io::Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.bytes())
+    }
+
+    /// Writes the image to `path`, as PPM if the extension is `.ppm` and as PNG otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match Format::from_path(path) {
+            Format::Ppm => fs::write(path, self.to_ppm()),
+            Format::Png => self
+                .write_png(BufWriter::new(fs::File::create(path)?))
+                .map_err(io::Error::other),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Writes one numbered image per simulation step into a folder, e.g.
+/// `data/visualizations/06-1/00042.png`, ready to be stitched together with `ffmpeg`.
+#[derive(Debug, Clone)]
+pub struct ImageSequence {
+    dir: Option<PathBuf>,
+    format: Format,
+    every: usize,
+    steps: usize,
+    written: usize,
+}
+
+impl ImageSequence {
+    /// Creates a sequence writing PNG images into `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            format: Format::Png,
+            every: 1,
+            steps: 0,
+            written: 0,
+        }
+    }
+
+    /// Creates a sequence that ignores every image.
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            ..Self::new("")
+        }
+    }
+
+    /// Creates a sequence for one part of a day that is only enabled if `--render` was passed
+    /// to the solution.
+    pub fn from_args(day: Day, part: u8) -> Self {
+        if render_requested() {
+            Self::new(Path::new(VISUALIZATIONS_DIR).join(format!("{day}-{part}")))
+        } else {
+            Self::disabled()
+        }
+    }
+
+    /// Writes PPM images instead of PNG images.
+    pub fn ppm(mut self) -> Self {
+        self.format = Format::Ppm;
+        self
+    }
+
+    /// Only writes every `n`th captured image.
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// The number of images written so far.
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// Writes the image built by `f` for the current step. `f` is only called if the image is
+    /// kept, so building an expensive image costs nothing when the sequence is disabled.
+    pub fn capture_with(&mut self, f: impl FnOnce() -> Image) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        if self.steps.is_multiple_of(self.every) {
+            let path = dir.join(format!("{:05}.{}", self.written, self.format.extension()));
+            f().save(path)?;
+            self.written += 1;
+        }
+        self.steps += 1;
+        Ok(())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        let grid = Grid::from_vec(vec![true, false, false, true], 2);
+        Image::from_grid(&grid, 3, |&on| if on { Rgb::WHITE } else { Rgb::BLACK })
+    }
+
+    #[test]
+    fn scales_cells() {
+        let image = checkerboard();
+        assert_eq!((image.width(), image.height()), (6, 6));
+        assert_eq!(image.pixel(2, 2), Rgb::WHITE);
+        assert_eq!(image.pixel(3, 2), Rgb::BLACK);
+        assert_eq!(image.pixel(5, 5), Rgb::WHITE);
+    }
+
+    #[test]
+    fn encodes_ppm_and_png() {
+        let image = checkerboard();
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 6 * 3);
+
+        let png = image.to_png();
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (6, 6));
+        assert_eq!(&buf[..info.buffer_size()], image.bytes().as_slice());
+    }
+
+    #[test]
+    fn uses_renderer_colors() {
+        let mut view = GridRenderer::from_lines("#.\n..");
+        view.highlight([(1, 1)], Color::Blue).dim_unstyled();
+        let image = Image::from_renderer(&view, 1);
+        assert_eq!(image.pixel(0, 0), Rgb::GRAY.dim());
+        assert_eq!(image.pixel(1, 0), Rgb::BLACK);
+        assert_eq!(image.pixel(1, 1), Color::Blue.into());
+    }
+}
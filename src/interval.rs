@@ -0,0 +1,504 @@
+//! Sets and maps of half-open ranges, for span puzzles (disk compaction, seed ranges, ...).
+//!
+//! Ranges are stored by their start in a [`BTreeMap`], so inserting, removing and looking up a
+//! point take O(log n + k) for k ranges touched. Ranges never overlap: touching ranges are merged
+//! in an [`IntervalSet`], and in a [`RangeMap`] if they map to the same value.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::iter::Sum;
+use std::ops::{Range, Sub};
+
+/// Which range an allocation picks among the ones long enough for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fit {
+    /// The lowest range.
+    First,
+    /// The shortest range, the lowest one on ties.
+    Best,
+    /// The longest range, the lowest one on ties.
+    Worst,
+}
+
+impl Fit {
+    pub const ALL: [Fit; 3] = [Fit::First, Fit::Best, Fit::Worst];
+}
+
+impl Display for Fit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Fit::First => "first fit",
+            Fit::Best => "best fit",
+            Fit::Worst => "worst fit",
+        };
+        f.pad(name)
+    }
+}
+
+/// A set of values stored as disjoint, non-adjacent half-open ranges.
+///
+/// ```
+/// # use advent_of_code::interval::IntervalSet;
+/// let mut free: IntervalSet<usize> = [2..5, 8..9, 12..16].into_iter().collect();
+/// free.remove(3..4);
+/// assert_eq!(free.iter().collect::<Vec<_>>(), [2..3, 4..5, 8..9, 12..16]);
+/// assert_eq!(free.first_fit(2), Some(12..16));
+/// assert_eq!(free.gaps(0..10).collect::<Vec<_>>(), [0..2, 3..4, 5..8, 9..10]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    /// Start to end of every range.
+    ranges: BTreeMap<T, T>,
+    /// Length to the starts of every range with that length, for fit searches.
+    by_length: BTreeMap<T, BTreeSet<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+            by_length: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Sub<Output = T>,
+{
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_raw(&mut self, start: T, end: T) {
+        self.ranges.insert(start, end);
+        self.by_length.entry(end - start).or_default().insert(start);
+    }
+
+    fn remove_raw(&mut self, start: T) -> T {
+        let end = self.ranges.remove(&start).unwrap();
+        let starts = self.by_length.get_mut(&(end - start)).unwrap();
+        starts.remove(&start);
+        if starts.is_empty() {
+            self.by_length.remove(&(end - start));
+        }
+        end
+    }
+
+    /// Adds every value in `range`, merging it with the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..=start).next_back() {
+            if prev_end >= start {
+                start = prev_start;
+                end = end.max(self.remove_raw(prev_start));
+            }
+        }
+        while let Some((&next_start, _)) = self.ranges.range(start..=end).next() {
+            end = end.max(self.remove_raw(next_start));
+        }
+        self.add_raw(start, end);
+    }
+
+    /// Removes every value in `range`, splitting the ranges it cuts through.
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..range.start).next_back() {
+            if prev_end > range.start {
+                self.remove_raw(prev_start);
+                self.add_raw(prev_start, range.start);
+                if prev_end > range.end {
+                    self.add_raw(range.end, prev_end);
+                }
+            }
+        }
+        while let Some((&next_start, _)) = self.ranges.range(range.clone()).next() {
+            let next_end = self.remove_raw(next_start);
+            if next_end > range.end {
+                self.add_raw(range.end, next_end);
+            }
+        }
+    }
+
+    /// The range containing `value`, if any.
+    pub fn range_of(&self, value: T) -> Option<Range<T>> {
+        let (&start, &end) = self.ranges.range(..=value).next_back()?;
+        (value < end).then_some(start..end)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.range_of(value).is_some()
+    }
+
+    /// Whether every value in `range` is in the set.
+    pub fn covers(&self, range: Range<T>) -> bool {
+        range.is_empty()
+            || self
+                .range_of(range.start)
+                .is_some_and(|found| found.end >= range.end)
+    }
+
+    /// Whether any value in `range` is in the set.
+    pub fn overlaps(&self, range: Range<T>) -> bool {
+        !range.is_empty()
+            && (self.contains(range.start) || self.ranges.range(range).next().is_some())
+    }
+
+    /// Iterates over the ranges in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Range<T>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// The number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn covered(&self) -> T
+    where
+        T: Sum,
+    {
+        self.iter().map(|range| range.end - range.start).sum()
+    }
+
+    /// Iterates over the parts of `within` that are not in the set, in ascending order.
+    pub fn gaps(&self, within: Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        let first = self.range_of(within.start).map(|range| range.start);
+        let mut cursor = within.start;
+        let end = within.end.max(within.start);
+        let mut ranges = self.ranges.range(first.unwrap_or(within.start)..end);
+        std::iter::from_fn(move || {
+            while cursor < within.end {
+                match ranges.next() {
+                    Some((&start, &end)) if start <= cursor => cursor = cursor.max(end),
+                    Some((&start, &end)) => {
+                        let gap = cursor..start;
+                        cursor = end;
+                        return Some(gap);
+                    }
+                    None => {
+                        let gap = cursor..within.end;
+                        cursor = within.end;
+                        return Some(gap);
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// The lowest range with at least `length` values. Takes the lowest range of every length
+    /// that fits, so it costs O(d log n) for d distinct lengths: O(log n) when lengths are
+    /// bounded, like the digits of a disk map.
+    pub fn first_fit(&self, length: T) -> Option<Range<T>> {
+        let start = self
+            .by_length
+            .range(length..)
+            .filter_map(|(_, starts)| starts.first().copied())
+            .min()?;
+        self.range_of(start)
+    }
+
+    /// The shortest range with at least `length` values, the lowest one on ties.
+    pub fn best_fit(&self, length: T) -> Option<Range<T>> {
+        let (_, starts) = self.by_length.range(length..).next()?;
+        self.range_of(*starts.first()?)
+    }
+
+    /// The longest range, the lowest one on ties, if it has at least `length` values.
+    pub fn worst_fit(&self, length: T) -> Option<Range<T>> {
+        let (&longest, starts) = self.by_length.last_key_value()?;
+        if longest < length {
+            return None;
+        }
+        self.range_of(*starts.first()?)
+    }
+
+    /// The range `fit` picks among the ones with at least `length` values that start before
+    /// `limit`, like free space to the left of a file. Like [`Self::first_fit`], looks at the
+    /// lowest range of each length rather than at every range.
+    pub fn fit_before(&self, fit: Fit, length: T, limit: T) -> Option<Range<T>> {
+        let mut candidates = self
+            .by_length
+            .range(length..)
+            .filter_map(|(_, starts)| starts.first().copied())
+            .filter(|&start| start < limit);
+        let start = match fit {
+            Fit::First => candidates.min(),
+            Fit::Best => candidates.next(),
+            Fit::Worst => candidates.last(),
+        }?;
+        self.range_of(start)
+    }
+
+    /// The values in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let (start, end) = (x.start.max(y.start), x.end.min(y.end));
+            if start < end {
+                result.add_raw(start, end);
+            }
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        result
+    }
+
+    /// The values in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        result.extend(other.iter());
+        result
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Sub<Output = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T> Extend<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Sub<Output = T>,
+{
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A map from disjoint half-open ranges of keys to values.
+///
+/// ```
+/// # use advent_of_code::interval::RangeMap;
+/// let mut disk = RangeMap::new();
+/// disk.insert(0..2, 'a');
+/// disk.insert(5..8, 'b');
+/// disk.insert(1..6, 'c');
+/// assert_eq!(disk.iter().collect::<Vec<_>>(), [(0..1, &'a'), (1..6, &'c'), (6..8, &'b')]);
+/// assert_eq!(disk.get(7), Some(&'b'));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeMap<K, V> {
+    /// Start to end and value of every range.
+    ranges: BTreeMap<K, (K, V)>,
+}
+
+impl<K, V> Default for RangeMap<K, V> {
+    fn default() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K, V> RangeMap<K, V>
+where
+    K: Copy + Ord,
+    V: Clone + PartialEq,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps every key in `range` to `value`, replacing what was there before. Merges with the
+    /// neighbouring ranges if they touch it and map to the same value.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+        self.remove(range.clone());
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((&prev_start, (prev_end, prev_value))) = self.ranges.range(..start).next_back()
+        {
+            if *prev_end == start && *prev_value == value {
+                self.ranges.remove(&prev_start);
+                start = prev_start;
+            }
+        }
+        if let Some((next_end, next_value)) = self.ranges.get(&end) {
+            if *next_value == value {
+                let next_end = *next_end;
+                self.ranges.remove(&end);
+                end = next_end;
+            }
+        }
+        self.ranges.insert(start, (end, value));
+    }
+
+    /// Removes every key in `range`, splitting the ranges it cuts through.
+    pub fn remove(&mut self, range: Range<K>) {
+        if range.is_empty() {
+            return;
+        }
+        if let Some((&prev_start, (prev_end, _))) = self.ranges.range(..range.start).next_back() {
+            if *prev_end > range.start {
+                let (prev_end, value) = self.ranges.remove(&prev_start).unwrap();
+                if prev_end > range.end {
+                    self.ranges.insert(range.end, (prev_end, value.clone()));
+                }
+                self.ranges.insert(prev_start, (range.start, value));
+            }
+        }
+        while let Some((&next_start, _)) = self.ranges.range(range.clone()).next() {
+            let (next_end, value) = self.ranges.remove(&next_start).unwrap();
+            if next_end > range.end {
+                self.ranges.insert(range.end, (next_end, value));
+            }
+        }
+    }
+
+    /// The value `key` maps to, if any.
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.get_range(key).map(|(_, value)| value)
+    }
+
+    /// The range containing `key` and the value it maps to, if any.
+    pub fn get_range(&self, key: K) -> Option<(Range<K>, &V)> {
+        let (&start, (end, value)) = self.ranges.range(..=key).next_back()?;
+        (key < *end).then_some((start..*end, value))
+    }
+
+    /// Iterates over the ranges and their values in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Range<K>, &V)> + '_ {
+        self.ranges
+            .iter()
+            .map(|(&start, (end, value))| (start..*end, value))
+    }
+
+    /// The number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Iterates over the parts of `within` that no range covers, in ascending order.
+    pub fn gaps(&self, within: Range<K>) -> impl Iterator<Item = Range<K>> + '_ {
+        let mut cursor = within.start;
+        let mut ranges = self
+            .iter()
+            .skip_while(move |(range, _)| range.end <= within.start);
+        std::iter::from_fn(move || {
+            while cursor < within.end {
+                match ranges.next() {
+                    Some((range, _)) if range.start <= cursor => cursor = cursor.max(range.end),
+                    Some((range, _)) => {
+                        let gap = cursor..range.start.min(within.end);
+                        cursor = range.end;
+                        return Some(gap);
+                    }
+                    None => {
+                        let gap = cursor..within.end;
+                        cursor = within.end;
+                        return Some(gap);
+                    }
+                }
+            }
+            None
+        })
+    }
+}
+
+impl<K, V> FromIterator<(Range<K>, V)> for RangeMap<K, V>
+where
+    K: Copy + Ord,
+    V: Clone + PartialEq,
+{
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (range, value) in iter {
+            map.insert(range, value);
+        }
+        map
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges<T: Copy + Ord + Sub<Output = T>>(set: &IntervalSet<T>) -> Vec<Range<T>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn merges_and_splits() {
+        let mut set: IntervalSet<i64> = [0..3, 5..7, 3..4, 10..12].into_iter().collect();
+        assert_eq!(ranges(&set), [0..4, 5..7, 10..12]);
+        set.insert(6..10);
+        assert_eq!(ranges(&set), [0..4, 5..12]);
+        set.remove(2..6);
+        set.remove(8..9);
+        assert_eq!(ranges(&set), [0..2, 6..8, 9..12]);
+        assert_eq!(set.covered(), 7);
+        assert!(set.covers(9..12) && !set.covers(7..10));
+        assert!(set.overlaps(-5..1) && !set.overlaps(2..6));
+    }
+
+    #[test]
+    fn searches_by_length() {
+        let set: IntervalSet<usize> = [0..3, 5..6, 8..10, 12..15].into_iter().collect();
+        assert_eq!(set.first_fit(2), Some(0..3));
+        assert_eq!(set.best_fit(2), Some(8..10));
+        assert_eq!(set.worst_fit(2), Some(0..3));
+        assert_eq!(set.best_fit(4), None);
+        assert_eq!(set.worst_fit(4), None);
+        assert_eq!(set.gaps(1..9).collect::<Vec<_>>(), [3..5, 6..8]);
+        assert_eq!(set.fit_before(Fit::First, 2, 10), Some(0..3));
+        assert_eq!(set.fit_before(Fit::Best, 3, 10), Some(0..3));
+        assert_eq!(set.fit_before(Fit::Worst, 1, 5), Some(0..3));
+        assert_eq!(set.fit_before(Fit::First, 3, 0), None);
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a: IntervalSet<i32> = [0..5, 10..15].into_iter().collect();
+        let b: IntervalSet<i32> = [3..12, 14..20].into_iter().collect();
+        assert_eq!(ranges(&a.intersection(&b)), [3..5, 10..12, 14..15]);
+        assert_eq!(a.union(&b).range_of(7), Some(0..20));
+        assert_eq!(a.union(&b).len(), 1);
+    }
+
+    #[test]
+    fn maps_ranges() {
+        let mut map: RangeMap<u32, char> = [(0..10, 'a')].into_iter().collect();
+        map.insert(3..5, 'b');
+        assert_eq!(map.len(), 3);
+        map.insert(3..5, 'a');
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(0..10, &'a')]);
+        map.remove(2..4);
+        assert_eq!(map.get_range(5), Some((4..10, &'a')));
+        assert_eq!(map.get(3), None);
+        assert_eq!(map.gaps(0..12).collect::<Vec<_>>(), [2..4, 10..12]);
+    }
+}
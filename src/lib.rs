@@ -1,3 +1,20 @@
 pub mod template;
 
 // Use this file to add helper functions and additional modules.
+pub mod bitset;
+pub mod counter;
+pub mod crosscheck;
+pub mod cycle;
+pub mod direction;
+pub mod equation;
+pub mod generate;
+pub mod graph;
+pub mod image;
+pub mod interval;
+pub mod num;
+pub mod parse;
+pub mod pathfind;
+pub mod recorder;
+pub mod render;
+pub mod vm;
+pub mod wordsearch;
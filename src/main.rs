@@ -1,4 +1,4 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{all, download, generate, read, scaffold, solve, time};
 use args::{parse, AppArguments};
 
 #[cfg(feature = "today")]
@@ -7,7 +7,7 @@ use advent_of_code::template::Day;
 use std::process;
 
 mod args {
-    use advent_of_code::template::Day;
+    use advent_of_code::template::{commands::solve::VisualizeFlags, Day};
     use std::process;
 
     pub enum AppArguments {
@@ -17,6 +17,11 @@ mod args {
         Read {
             day: Day,
         },
+        Generate {
+            day: Day,
+            size: Option<usize>,
+            seed: u64,
+        },
         Scaffold {
             day: Day,
             download: bool,
@@ -27,6 +32,8 @@ mod args {
             release: bool,
             dhat: bool,
             submit: Option<u8>,
+            visualize: VisualizeFlags,
+            options: Vec<String>,
         },
         All {
             release: bool,
@@ -41,7 +48,13 @@ mod args {
     }
 
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
-        let mut args = pico_args::Arguments::from_env();
+        // Everything after `--` is passed on to the solution untouched.
+        let mut raw: Vec<String> = std::env::args().skip(1).collect();
+        let options = match raw.iter().position(|arg| arg == "--") {
+            Some(index) => raw.split_off(index).split_off(1),
+            None => vec![],
+        };
+        let mut args = pico_args::Arguments::from_vec(raw.into_iter().map(Into::into).collect());
 
         let app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
@@ -63,6 +76,11 @@ mod args {
             Some("read") => AppArguments::Read {
                 day: args.free_from_str()?,
             },
+            Some("gen") => AppArguments::Generate {
+                day: args.free_from_str()?,
+                size: args.opt_value_from_str("--size")?,
+                seed: args.opt_value_from_str("--seed")?.unwrap_or(0),
+            },
             Some("scaffold") => AppArguments::Scaffold {
                 day: args.free_from_str()?,
                 download: args.contains("--download"),
@@ -73,6 +91,14 @@ mod args {
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
+                visualize: VisualizeFlags {
+                    visualize: args.contains("--visualize"),
+                    record: args.contains("--record"),
+                    replay: args.opt_value_from_str("--replay")?,
+                    render: args.contains("--render"),
+                    explain: args.contains("--explain"),
+                },
+                options,
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -106,6 +132,7 @@ fn main() {
             AppArguments::Time { day, all, store } => time::handle(day, all, store),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
+            AppArguments::Generate { day, size, seed } => generate::handle(day, size, seed),
             AppArguments::Scaffold {
                 day,
                 download,
@@ -121,7 +148,9 @@ fn main() {
                 release,
                 dhat,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+                visualize,
+                options,
+            } => solve::handle(day, release, dhat, submit, visualize, &options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
@@ -0,0 +1,425 @@
+//! Digit manipulation and number theory for integer puzzles.
+//!
+//! Everything is generic over the primitive integer types through [`Int`], so the same helper
+//! works for `u32` page numbers and `u128` stone counts. Digit helpers work on [`Unsigned`]
+//! types and never allocate. [`Modular`] counts modulo a prime when even `u128` is too small.
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, BitAnd, BitXor, Div, Mul, Rem, Shr, Sub};
+
+/// A primitive integer type.
+pub trait Int:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitAnd<Output = Self>
+    + BitXor<Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TEN: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+}
+
+/// A primitive unsigned integer type.
+pub trait Unsigned: Int {
+    fn checked_ilog10(self) -> Option<u32>;
+}
+
+/// A primitive signed integer type.
+pub trait Signed: Int {}
+
+macro_rules! impl_int {
+    ($($t:ty),* ; $marker:ident) => {$(
+        impl Int for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const TEN: Self = 10;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            fn checked_pow(self, exp: u32) -> Option<Self> {
+                <$t>::checked_pow(self, exp)
+            }
+        }
+        impl_int!(@marker $t ; $marker);
+    )*};
+    (@marker $t:ty ; Unsigned) => {
+        impl Unsigned for $t {
+            fn checked_ilog10(self) -> Option<u32> {
+                <$t>::checked_ilog10(self)
+            }
+        }
+    };
+    (@marker $t:ty ; Signed) => {
+        impl Signed for $t {}
+    };
+}
+
+impl_int!(u8, u16, u32, u64, u128, usize; Unsigned);
+impl_int!(i8, i16, i32, i64, i128, isize; Signed);
+
+/* -------------------------------------------------------------------------- */
+
+/// The number of decimal digits in `n`, counting `0` as one digit.
+pub fn digit_count<T: Unsigned>(n: T) -> u32 {
+    n.checked_ilog10().map_or(1, |log| log + 1)
+}
+
+/// The digits of `a` followed by the digits of `b`, e.g. `concat(12, 345) == Some(12345)`, or
+/// [`None`] if that overflows.
+pub fn concat<T: Unsigned>(a: T, b: T) -> Option<T> {
+    a.checked_mul(T::TEN.checked_pow(digit_count(b))?)?
+        .checked_add(b)
+}
+
+/// Splits off the last `k` digits of `n`, e.g. `split_at_digit(12345, 2) == (123, 45)`.
+pub fn split_at_digit<T: Unsigned>(n: T, k: u32) -> (T, T) {
+    match T::TEN.checked_pow(k) {
+        Some(shift) => (n / shift, n % shift),
+        None => (T::ZERO, n),
+    }
+}
+
+/// Iterates over the digits of `n`, most significant first.
+///
+/// ```
+/// # use advent_of_code::num::digits;
+/// assert_eq!(digits(9075u32).collect::<Vec<_>>(), [9, 0, 7, 5]);
+/// assert_eq!(digits(0u8).count(), 1);
+/// ```
+pub fn digits<T: Unsigned>(n: T) -> Digits<T> {
+    Digits {
+        n,
+        divisor: T::TEN.checked_pow(digit_count(n) - 1),
+    }
+}
+
+/// Iterator returned by [`digits`].
+#[derive(Debug, Clone)]
+pub struct Digits<T> {
+    n: T,
+    divisor: Option<T>,
+}
+
+impl<T: Unsigned> Iterator for Digits<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let divisor = self.divisor?;
+        self.divisor = (divisor > T::ONE).then(|| divisor / T::TEN);
+        Some(self.n / divisor % T::TEN)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// The average of `a` and `b` rounded down, without overflowing.
+pub fn midpoint<T: Int>(a: T, b: T) -> T {
+    // The shared bits, plus half of the bits only one of them has.
+    (a & b) + ((a ^ b) >> 1)
+}
+
+fn abs<T: Int>(n: T) -> T {
+    if n < T::ZERO {
+        T::ZERO - n
+    } else {
+        n
+    }
+}
+
+/// The greatest common divisor of the absolute values of `a` and `b`.
+pub fn gcd<T: Int>(a: T, b: T) -> T {
+    let (mut a, mut b) = (abs(a), abs(b));
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The least common multiple of the absolute values of `a` and `b`.
+pub fn lcm<T: Int>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+    abs(a) / gcd(a, b) * abs(b)
+}
+
+/// `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y == g`.
+pub fn extended_gcd<T: Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (T::ZERO - old_r, T::ZERO - old_x, T::ZERO - old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `a` modulo `m`, always in `0..m` for a positive `m`.
+pub fn rem_euclid<T: Int>(a: T, m: T) -> T {
+    let r = a % m;
+    if r < T::ZERO {
+        r + abs(m)
+    } else {
+        r
+    }
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, or [`None`] if `a` and `m` are not coprime.
+pub fn mod_inverse<T: Signed>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(rem_euclid(a, m), m);
+    (g == T::ONE).then(|| rem_euclid(x, m))
+}
+
+/// `base.pow(exp) % modulus`, by repeated squaring.
+pub fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// Solves the system `x ≡ residue (mod modulus)` for every `(residue, modulus)` pair with the
+/// Chinese remainder theorem, returning the smallest non-negative `x` and the combined modulus.
+///
+/// Moduli do not need to be coprime. Returns [`None`] if a modulus is not positive, if the
+/// congruences contradict each other or if the combined modulus does not fit an `i64`.
+///
+/// ```
+/// # use advent_of_code::num::crt;
+/// assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+/// assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+/// assert_eq!(crt(&[(0, 4), (1, 6)]), None);
+/// assert_eq!(crt(&[(2, 3), (1, 0)]), None);
+/// ```
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let (mut x, mut m) = (0i128, 1i128);
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 {
+            return None;
+        }
+        let (residue, modulus) = (residue as i128, modulus as i128);
+        let (g, p, _) = extended_gcd(m, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = modulus / g;
+        let t = rem_euclid(diff / g % step * p % step, step);
+        x += m * t;
+        m *= step;
+        x = rem_euclid(x, m);
+        if m > i64::MAX as i128 {
+            return None;
+        }
+    }
+    Some((x as i64, m as i64))
+}
+
+/// An integer modulo a modulus chosen at run time, for counts that would overflow otherwise.
+///
+/// The [`Default`] value is a zero without a modulus that takes on the modulus of whatever is
+/// added to it, so `Modular` works as the count type of a [`Counter`](crate::counter::Counter).
+///
+/// ```
+/// # use advent_of_code::num::Modular;
+/// let mut sum = Modular::default();
+/// sum += Modular::new(6, 7);
+/// sum += Modular::new(5, 7);
+/// assert_eq!(sum.value(), 4);
+/// assert_eq!((sum * Modular::new(2, 7)).to_string(), "1 (mod 7)");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modular {
+    value: u64,
+    modulus: u64,
+}
+
+impl Modular {
+    /// `value` modulo `modulus`.
+    ///
+    /// # Panics
+    ///
+    /// If `modulus` is 0.
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+        Self {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    /// The modulus, or [`None`] for a [`Default`] zero.
+    pub fn modulus(self) -> Option<u64> {
+        (self.modulus > 0).then_some(self.modulus)
+    }
+
+    /// The modulus shared by `self` and `other`.
+    fn common_modulus(self, other: Self) -> u64 {
+        match (self.modulus, other.modulus) {
+            (0, m) | (m, 0) => m,
+            (a, b) => {
+                assert_eq!(a, b, "cannot combine numbers with different moduli");
+                a
+            }
+        }
+    }
+}
+
+impl Add for Modular {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let modulus = self.common_modulus(rhs);
+        if modulus == 0 {
+            return Self::default();
+        }
+        let sum = (self.value as u128 + rhs.value as u128) % modulus as u128;
+        Self {
+            value: sum as u64,
+            modulus,
+        }
+    }
+}
+
+impl AddAssign for Modular {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Mul for Modular {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let modulus = self.common_modulus(rhs);
+        if modulus == 0 {
+            return Self::default();
+        }
+        let product = self.value as u128 * rhs.value as u128 % modulus as u128;
+        Self {
+            value: product as u64,
+            modulus,
+        }
+    }
+}
+
+impl Display for Modular {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.modulus() {
+            Some(modulus) => write!(f, "{} (mod {modulus})", self.value),
+            None => write!(f, "0"),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manipulates_digits() {
+        assert_eq!(digit_count(0u32), 1);
+        assert_eq!(digit_count(99u8), 2);
+        assert_eq!(digit_count(u128::MAX), 39);
+        assert_eq!(concat(12u64, 345), Some(12345));
+        assert_eq!(concat(1u64, 0), Some(10));
+        assert_eq!(concat(255u8, 1), None);
+        assert_eq!(split_at_digit(12345u32, 2), (123, 45));
+        assert_eq!(split_at_digit(7u8, 5), (0, 7));
+        assert_eq!(digits(u64::MAX).count(), 20);
+        assert_eq!(digits(9075u32).collect::<Vec<_>>(), [9, 0, 7, 5]);
+        assert_eq!(digits(0u8).collect::<Vec<_>>(), [0]);
+        assert_eq!(digits(100u16).collect::<Vec<_>>(), [1, 0, 0]);
+    }
+
+    #[test]
+    fn finds_midpoints() {
+        assert_eq!(midpoint(0usize, 5), 2);
+        assert_eq!(midpoint(u8::MAX, u8::MAX - 2), u8::MAX - 1);
+        assert_eq!(midpoint(i32::MIN, i32::MAX), -1);
+        assert_eq!(midpoint(-3i8, 0), -2);
+    }
+
+    #[test]
+    fn solves_number_theory() {
+        assert_eq!(gcd(12u32, 18), 6);
+        assert_eq!(gcd(-12i64, 18), 6);
+        assert_eq!(lcm(4u64, 6), 12);
+        let (g, x, y) = extended_gcd(240i64, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+        assert_eq!(mod_inverse(3i64, 11), Some(4));
+        assert_eq!(mod_inverse(2i64, 4), None);
+        assert_eq!(mod_pow(2, 62, 1_000_000_007), (1u64 << 62) % 1_000_000_007);
+        assert_eq!(mod_pow(u64::MAX, u64::MAX, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn solves_congruences() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 5)]), Some((4, 5)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        // Not coprime, but consistent.
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        // Contradictory: even and odd at once.
+        assert_eq!(crt(&[(0, 4), (1, 6)]), None);
+        assert_eq!(crt(&[(2, 3), (1, 0)]), None);
+        assert_eq!(crt(&[(0, 0)]), None);
+        assert_eq!(crt(&[(2, 3), (1, -5)]), None);
+        // Consecutive numbers are coprime, so the combined modulus overflows an i64.
+        assert_eq!(crt(&[(0, i64::MAX), (0, i64::MAX - 1)]), None);
+    }
+
+    #[test]
+    fn adds_modulo_a_runtime_modulus() {
+        let big = Modular::new(u64::MAX - 1, u64::MAX);
+        assert_eq!((big + big).value(), u64::MAX - 2);
+        assert_eq!((big * big).value(), 1);
+        assert_eq!(Modular::default() + Modular::default(), Modular::default());
+        assert_eq!(Modular::default().modulus(), None);
+
+        let mut sum = Modular::default();
+        sum += Modular::new(6, 7);
+        sum += Modular::new(5, 7);
+        assert_eq!(sum.value(), 4);
+        assert_eq!((sum * Modular::new(2, 7)).to_string(), "1 (mod 7)");
+        assert_eq!(Modular::default().to_string(), "0");
+    }
+}
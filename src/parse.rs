@@ -0,0 +1,211 @@
+//! Reusable `nom` parsers for the input shapes that come up again and again.
+//!
+//! Every combinator works on `&str` with nom's default error type, so they compose with
+//! hand-written parsers in a solution. Use [`run`] to parse a whole input and get a
+//! [`ParseError`] that points at the line and column where parsing stopped.
+use std::fmt::Display;
+use std::str::FromStr;
+
+use grid::Grid;
+use miette::{Diagnostic, SourceSpan};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, multispace0, satisfy, space0, space1},
+    combinator::{all_consuming, map_res, opt, recognize},
+    error::ErrorKind,
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, separated_pair, terminated, tuple},
+    IResult, Parser,
+};
+
+/// Parses a (optionally negative) integer into any type implementing [`FromStr`].
+pub fn number<N: FromStr>(input: &str) -> IResult<&str, N> {
+    map_res(recognize(pair(opt(char('-')), digit1)), str::parse)(input)
+}
+
+/// Parses a single decimal digit, e.g. one block of a day 9 disk map.
+pub fn digit<N: From<u8>>(input: &str) -> IResult<&str, N> {
+    map_res(satisfy(|c| c.is_ascii_digit()), |c: char| {
+        c.to_digit(10).map(|d| N::from(d as u8)).ok_or(())
+    })(input)
+}
+
+/// Parses numbers separated by spaces or tabs on a single line, e.g. `7 6 4 2 1`.
+pub fn number_row<N: FromStr>(input: &str) -> IResult<&str, Vec<N>> {
+    separated_list1(space1, number)(input)
+}
+
+/// Parses numbers separated by commas, e.g. `75,47,61,53,29`.
+pub fn comma_list<N: FromStr>(input: &str) -> IResult<&str, Vec<N>> {
+    separated_list1(tuple((space0, char(','), space0)), number)(input)
+}
+
+/// Parses two values around a single separator character, e.g. `47|53` with `separator = '|'`.
+pub fn separated<'a, A, B>(
+    first: impl Parser<&'a str, A, nom::error::Error<&'a str>>,
+    separator: char,
+    second: impl Parser<&'a str, B, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (A, B)> {
+    separated_pair(first, char(separator), second)
+}
+
+/// Parses a `key: values` line, e.g. `190: 10 19` with `number` and `number_row`.
+pub fn key_values<'a, K, V>(
+    key: impl Parser<&'a str, K, nom::error::Error<&'a str>>,
+    values: impl Parser<&'a str, V, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (K, V)> {
+    separated_pair(key, pair(char(':'), space0), values)
+}
+
+/// Parses one item per line, separated by line endings.
+pub fn lines<'a, O>(
+    line: impl Parser<&'a str, O, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(line_ending, line)
+}
+
+/// Parses blocks of text separated by a blank line, e.g. day 5's rules and updates.
+pub fn sections<'a, O>(
+    section: impl Parser<&'a str, O, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(pair(line_ending, many1(line_ending)), section)
+}
+
+/// Parses two differently shaped blocks separated by a blank line.
+pub fn two_sections<'a, A, B>(
+    first: impl Parser<&'a str, A, nom::error::Error<&'a str>>,
+    second: impl Parser<&'a str, B, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (A, B)> {
+    separated_pair(first, pair(line_ending, many1(line_ending)), second)
+}
+
+/// Parses a literal `name(` ... `)` call around an inner parser, e.g. `mul(2,4)`.
+pub fn call<'a, O>(
+    name: &'a str,
+    args: impl Parser<&'a str, O, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(pair(tag(name), char('(')), args, char(')'))
+}
+
+/// Runs `parser` over the whole of `input`, allowing only trailing whitespace to be left over.
+pub fn run<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, nom::error::Error<&'a str>>,
+) -> Result<O, ParseError> {
+    all_consuming(terminated(parser, multispace0))(input)
+        .map(|(_, output)| output)
+        .map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => ParseError::new(input, e.input, e.code),
+            nom::Err::Incomplete(_) => ParseError::new(input, "", ErrorKind::Eof),
+        })
+}
+
+/// Reads a rectangular block of characters into a grid, one row per line.
+pub fn char_grid(input: &str) -> Grid<char> {
+    let cols = input.lines().next().map_or(0, |line| line.chars().count());
+    Grid::from_vec(input.lines().flat_map(str::chars).collect(), cols)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// An error which can be returned by [`run`], located at the line and column where parsing failed.
+#[derive(Debug, Diagnostic)]
+#[diagnostic(code(advent_of_code::parse))]
+pub struct ParseError {
+    /// The 1-based line of the failure.
+    pub line: usize,
+    /// The 1-based column (in characters) of the failure.
+    pub column: usize,
+    /// The nom combinator that failed.
+    pub kind: ErrorKind,
+    #[source_code]
+    source_code: String,
+    #[label("{}", kind.description())]
+    span: SourceSpan,
+}
+
+impl ParseError {
+    fn new(input: &str, remaining: &str, kind: ErrorKind) -> Self {
+        let offset = input.len() - remaining.len();
+        let consumed = &input[..offset];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+        let column = consumed[line_start..].chars().count() + 1;
+        let span_len = remaining.chars().next().map_or(0, char::len_utf8);
+
+        Self {
+            line,
+            column,
+            kind,
+            source_code: input.to_string(),
+            span: (offset, span_len).into(),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parse error at line {}, column {}: {}",
+            self.line,
+            self.column,
+            self.kind.description()
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_common_shapes() {
+        assert_eq!(number_row::<i32>("7 -6\t4"), Ok(("", vec![7, -6, 4])));
+        assert_eq!(comma_list::<u32>("75,47, 61"), Ok(("", vec![75, 47, 61])));
+        assert_eq!(
+            separated(number::<u32>, '|', number::<u32>)("47|53"),
+            Ok(("", (47, 53)))
+        );
+        assert_eq!(
+            key_values(number::<u64>, number_row::<u64>)("190: 10 19"),
+            Ok(("", (190, vec![10, 19])))
+        );
+        assert_eq!(
+            call("mul", comma_list::<u32>)("mul(2,4)x"),
+            Ok(("x", vec![2, 4]))
+        );
+        assert_eq!(digit::<u32>("93"), Ok(("3", 9)));
+    }
+
+    #[test]
+    fn parses_sections() {
+        let input = "1|2\n3|4\n\n1,2\n3,4\n";
+        let (rules, updates) = run(
+            input,
+            two_sections(
+                lines(separated(number::<u32>, '|', number::<u32>)),
+                lines(comma_list::<u32>),
+            ),
+        )
+        .unwrap();
+        assert_eq!(rules, vec![(1, 2), (3, 4)]);
+        assert_eq!(updates, vec![vec![1, 2], vec![3, 4]]);
+
+        let blocks = run("1 2\n\n\n3", sections(lines(number_row::<u8>))).unwrap();
+        assert_eq!(blocks, vec![vec![vec![1, 2]], vec![vec![3]]]);
+    }
+
+    #[test]
+    fn reports_line_and_column() {
+        let err = run("1 2 3\n4 x 6\n", lines(number_row::<u32>)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(err
+            .to_string()
+            .starts_with("parse error at line 2, column 3"));
+    }
+}
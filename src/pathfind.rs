@@ -0,0 +1,204 @@
+//! Graph searches that run directly on a `grid::Grid`, built on the `pathfinding` crate.
+//!
+//! A [`GridGraph`] turns a grid into a graph where each cell is connected to its orthogonal (or
+//! also diagonal) neighbours, as long as a predicate on the two cell values allows the step.
+//! Positions are `(row, col)` indices, the same as `grid::Grid` and [`Dir4::step`].
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use grid::Grid;
+use pathfinding::num_traits::Zero;
+use pathfinding::prelude::{
+    astar, bfs, bfs_reach, build_path, count_paths, dijkstra, dijkstra_all,
+};
+
+use crate::direction::{Dir4, Dir8};
+
+/// A `(row, col)` grid index.
+pub type Pos = (usize, usize);
+
+/// The Manhattan distance between two positions, a good A* heuristic for orthogonal moves.
+pub fn manhattan(a: Pos, b: Pos) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// Rebuilds the path from the start to `target` out of the predecessors found by
+/// [`GridGraph::predecessors`]. The path only holds `target` if it was not reached.
+pub fn path_to(target: Pos, predecessors: &HashMap<Pos, (Pos, usize)>) -> Vec<Pos> {
+    build_path(&target, predecessors)
+}
+
+/// A grid seen as a graph.
+///
+/// ```
+/// # use advent_of_code::pathfind::GridGraph;
+/// # use grid::grid;
+/// let heights = grid![[0, 1, 2][9, 9, 3][6, 5, 4]];
+/// let hiking = GridGraph::new(&heights, |&from, &to| to == from + 1);
+/// assert_eq!(hiking.shortest_path((0, 0), |pos| heights[pos] == 6).unwrap().len(), 7);
+/// assert_eq!(hiking.count_paths((0, 0), |pos| heights[pos] == 4), 1);
+/// assert_eq!(hiking.reachable((1, 2)).len(), 4);
+/// ```
+pub struct GridGraph<'a, T, F> {
+    grid: &'a Grid<T>,
+    can_step: F,
+    diagonal: bool,
+}
+
+impl<'a, T, F> GridGraph<'a, T, F>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    /// Connects every cell to its orthogonal neighbours, if `can_step(from, to)` allows the step
+    /// from one cell value to the other.
+    pub fn new(grid: &'a Grid<T>, can_step: F) -> Self {
+        Self {
+            grid,
+            can_step,
+            diagonal: false,
+        }
+    }
+
+    /// Also connects every cell to its diagonal neighbours.
+    pub fn with_diagonals(mut self) -> Self {
+        self.diagonal = true;
+        self
+    }
+
+    /// The positions that can be stepped to from `pos`.
+    pub fn neighbors(&self, pos: Pos) -> Vec<Pos> {
+        let (rows, cols) = (self.grid.rows(), self.grid.cols());
+        let steps: Vec<Pos> = if self.diagonal {
+            Dir8::iter()
+                .filter_map(|d| d.step(pos, rows, cols))
+                .collect()
+        } else {
+            Dir4::iter()
+                .filter_map(|d| d.step(pos, rows, cols))
+                .collect()
+        };
+        steps
+            .into_iter()
+            .filter(|&next| (self.can_step)(&self.grid[pos], &self.grid[next]))
+            .collect()
+    }
+
+    fn weighted_neighbors<C>(&self, pos: Pos, cost: &impl Fn(Pos, Pos) -> C) -> Vec<(Pos, C)> {
+        self.neighbors(pos)
+            .into_iter()
+            .map(|next| (next, cost(pos, next)))
+            .collect()
+    }
+
+    /// A path with the fewest steps from `start` to a position where `goal` holds, including both
+    /// ends.
+    pub fn shortest_path(&self, start: Pos, mut goal: impl FnMut(Pos) -> bool) -> Option<Vec<Pos>> {
+        bfs(&start, |&pos| self.neighbors(pos), |&pos| goal(pos))
+    }
+
+    /// The number of steps to every position reachable from `start`, including `start` itself.
+    pub fn distances(&self, start: Pos) -> HashMap<Pos, usize> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[&pos] + 1;
+            for next in self.neighbors(pos) {
+                distances.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    distance
+                });
+            }
+        }
+        distances
+    }
+
+    /// For every position reachable from `start` (except `start`), the position it is reached from
+    /// on a shortest path, and the number of steps. Use [`path_to`] to turn it into paths.
+    pub fn predecessors(&self, start: Pos) -> HashMap<Pos, (Pos, usize)> {
+        dijkstra_all(&start, |&pos| self.weighted_neighbors(pos, &|_, _| 1))
+    }
+
+    /// Every position reachable from `start`, including `start` itself.
+    pub fn reachable(&self, start: Pos) -> HashSet<Pos> {
+        bfs_reach(start, |&pos| self.neighbors(pos)).collect()
+    }
+
+    /// The cheapest path from `start` to a position where `goal` holds, where stepping from one
+    /// position to the next costs `cost(from, to)`, and its total cost.
+    pub fn cheapest_path<C: Zero + Ord + Copy>(
+        &self,
+        start: Pos,
+        cost: impl Fn(Pos, Pos) -> C,
+        mut goal: impl FnMut(Pos) -> bool,
+    ) -> Option<(Vec<Pos>, C)> {
+        dijkstra(
+            &start,
+            |&pos| self.weighted_neighbors(pos, &cost),
+            |&pos| goal(pos),
+        )
+    }
+
+    /// Like [`Self::cheapest_path`] to a single `goal`, guided by a `heuristic` that must never
+    /// overestimate the remaining cost, such as [`manhattan`].
+    pub fn astar<C: Zero + Ord + Copy>(
+        &self,
+        start: Pos,
+        goal: Pos,
+        cost: impl Fn(Pos, Pos) -> C,
+        heuristic: impl Fn(Pos) -> C,
+    ) -> Option<(Vec<Pos>, C)> {
+        astar(
+            &start,
+            |&pos| self.weighted_neighbors(pos, &cost),
+            |&pos| heuristic(pos),
+            |&pos| pos == goal,
+        )
+    }
+
+    /// The number of distinct paths from `start` to positions where `goal` holds. The steps
+    /// allowed must not form a cycle, or this never returns.
+    pub fn count_paths(&self, start: Pos, mut goal: impl FnMut(Pos) -> bool) -> usize {
+        count_paths(start, |&pos| self.neighbors(pos), |&pos| goal(pos))
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::char_grid;
+
+    fn maze() -> Grid<char> {
+        char_grid("S.#.\n..#.\n#...\n...E")
+    }
+
+    #[test]
+    fn finds_paths_around_walls() {
+        let maze = maze();
+        let open = GridGraph::new(&maze, |_, &to| to != '#');
+        let path = open.shortest_path((0, 0), |pos| pos == (3, 3)).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(open.distances((0, 0))[&(3, 3)], 6);
+        let predecessors = open.predecessors((0, 0));
+        assert_eq!(path_to((3, 3), &predecessors).len(), 7);
+        assert_eq!(open.reachable((0, 0)).len(), 13);
+
+        let diagonal = GridGraph::new(&maze, |_, &to| to != '#').with_diagonals();
+        assert_eq!(diagonal.distances((0, 0))[&(3, 3)], 3);
+    }
+
+    #[test]
+    fn weighs_steps() {
+        let maze = maze();
+        let open = GridGraph::new(&maze, |_, &to| to != '#');
+        // Moving down costs ten times as much as any other move.
+        let cost = |from: Pos, to: Pos| if to.0 > from.0 { 10 } else { 1 };
+        let (_, total) = open
+            .cheapest_path((0, 0), cost, |pos| pos == (3, 3))
+            .unwrap();
+        let (_, guided) = open
+            .astar((0, 0), (3, 3), cost, |pos| manhattan(pos, (3, 3)))
+            .unwrap();
+        assert_eq!((total, guided), (33, 33));
+    }
+}
@@ -0,0 +1,261 @@
+//! Frame-by-frame recording of simulations, with terminal replay and asciinema export.
+//!
+//! Solutions create a recorder with [`Recorder::from_args`], which is only enabled when `solve`
+//! was started with `--record` or `--replay <fps>`, and call [`Recorder::finish`] at the end.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io, thread};
+
+use tinyjson::JsonValue;
+
+use crate::template::{flag, option, Day};
+
+/// Frames per second used for `.cast` files when `--replay` does not set a speed.
+const DEFAULT_FPS: f64 = 10.0;
+
+/// Where `--record` writes `.cast` files, relative to the repository root.
+pub const VISUALIZATIONS_DIR: &str = "data/visualizations";
+
+const ANSI_CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Collects one frame per simulation step.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    enabled: bool,
+    every: usize,
+    steps: usize,
+    frames: Vec<String>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recorder {
+    /// Creates a recorder that keeps every frame.
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            every: 1,
+            steps: 0,
+            frames: vec![],
+        }
+    }
+
+    /// Creates a recorder that ignores every frame.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    /// Creates a recorder that is only enabled if `--record` or `--replay` was passed to the solution.
+    pub fn from_args() -> Self {
+        if record_requested() || replay_fps().is_some() {
+            Self::new()
+        } else {
+            Self::disabled()
+        }
+    }
+
+    /// Only keeps every `n`th captured frame, to keep long simulations to a watchable length.
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Captures `frame` for the current step.
+    pub fn capture(&mut self, frame: impl Display) {
+        self.capture_with(|| frame);
+    }
+
+    /// Captures the frame built by `f` for the current step. `f` is only called if the frame is kept,
+    /// so building an expensive frame costs nothing when recording is disabled.
+    pub fn capture_with<D: Display>(&mut self, f: impl FnOnce() -> D) {
+        if !self.enabled {
+            return;
+        }
+        if self.steps.is_multiple_of(self.every) {
+            self.frames.push(f().to_string());
+        }
+        self.steps += 1;
+    }
+
+    /// Captures the final frame, even if it would otherwise be skipped by [`Recorder::every`].
+    pub fn capture_last(&mut self, frame: impl Display) {
+        self.capture_last_with(|| frame);
+    }
+
+    /// Captures the final frame built by `f`, which is only called when recording is enabled.
+    pub fn capture_last_with<D: Display>(&mut self, f: impl FnOnce() -> D) {
+        if self.enabled {
+            self.frames.push(f().to_string());
+        }
+    }
+
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Plays the frames back in the terminal, redrawing the screen `fps` times per second.
+    pub fn replay(&self, fps: f64) -> io::Result<()> {
+        let delay = Duration::from_secs_f64(1.0 / fps.max(0.001));
+        let mut stdout = stdout().lock();
+        for frame in &self.frames {
+            write!(stdout, "{ANSI_CLEAR_SCREEN}{frame}")?;
+            stdout.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+
+    /// Renders the frames as an asciicast v2 recording, showing `fps` frames per second.
+    pub fn to_asciicast(&self, fps: f64, title: &str) -> String {
+        let (width, height) = self.frames.iter().fold((1, 1), |(w, h), frame| {
+            let frame_width = frame.lines().map(visible_width).max().unwrap_or(0);
+            (w.max(frame_width), h.max(frame.lines().count()))
+        });
+
+        let header = JsonValue::from(HashMap::from([
+            ("version".to_string(), JsonValue::from(2.0)),
+            ("width".to_string(), JsonValue::from(width as f64)),
+            ("height".to_string(), JsonValue::from(height as f64)),
+            ("title".to_string(), JsonValue::from(title.to_string())),
+        ]));
+
+        let mut out = header.stringify().unwrap();
+        out.push('\n');
+        for (i, frame) in self.frames.iter().enumerate() {
+            let data = format!("{ANSI_CLEAR_SCREEN}{}", frame.replace('\n', "\r\n"));
+            let event = JsonValue::from(vec![
+                JsonValue::from(i as f64 / fps),
+                JsonValue::from("o".to_string()),
+                JsonValue::from(data),
+            ]);
+            out.push_str(&event.stringify().unwrap());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Writes the frames to an asciicast v2 file that can be played with `asciinema play`.
+    pub fn write_asciicast(&self, path: impl AsRef<Path>, fps: f64, title: &str) -> io::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_asciicast(fps, title))
+    }
+
+    /// Handles the `--record` and `--replay <fps>` flags once the simulation of `part` is done.
+    pub fn finish(&self, day: Day, part: u8) {
+        if !self.enabled {
+            return;
+        }
+        let fps = replay_fps();
+        if let Some(fps) = fps {
+            if let Err(e) = self.replay(fps) {
+                eprintln!("Failed to replay recording: {e}");
+            }
+        }
+        if record_requested() {
+            let path = recording_path(day, part);
+            let title = format!("Day {day} - Part {part}");
+            match self.write_asciicast(&path, fps.unwrap_or(DEFAULT_FPS), &title) {
+                Ok(()) => println!("Recorded {} frames to \"{}\"", self.len(), path.display()),
+                Err(e) => eprintln!("Failed to write recording: {e}"),
+            }
+        }
+    }
+}
+
+/// Whether the solution was started with `cargo solve <day> --record`.
+pub fn record_requested() -> bool {
+    flag("--record")
+}
+
+/// The speed passed with `cargo solve <day> --replay <fps>`, if any.
+pub fn replay_fps() -> Option<f64> {
+    option("--replay")
+}
+
+/// The `.cast` file `--record` writes for one part of a day, e.g. `data/visualizations/06-1.cast`.
+pub fn recording_path(day: Day, part: u8) -> PathBuf {
+    Path::new(VISUALIZATIONS_DIR).join(format!("{day}-{part}.cast"))
+}
+
+/// The number of characters on a line that take up space, ignoring ANSI escape sequences.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match (in_escape, c) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => width += 1,
+            (true, c) if c.is_ascii_alphabetic() => in_escape = false,
+            (true, _) => {}
+        }
+    }
+    width
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_every_nth_frame() {
+        let mut recorder = Recorder::new().every(3);
+        for i in 0..7 {
+            recorder.capture(i);
+        }
+        recorder.capture_last("end");
+        assert_eq!(recorder.frames(), ["0", "3", "6", "end"]);
+
+        let mut disabled = Recorder::disabled();
+        disabled.capture_with(|| -> &str { unreachable!() });
+        disabled.capture_last_with(|| -> &str { unreachable!() });
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn exports_asciicast() {
+        let mut recorder = Recorder::new();
+        recorder.capture("\x1b[31m#\x1b[0m..\n...");
+        recorder.capture("...\n.#.");
+        let cast = recorder.to_asciicast(2.0, "test");
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let header: JsonValue = lines[0].parse().unwrap();
+        assert_eq!(header["version"], JsonValue::from(2.0));
+        assert_eq!(header["width"], JsonValue::from(3.0));
+        assert_eq!(header["height"], JsonValue::from(2.0));
+
+        let event: JsonValue = lines[2].parse().unwrap();
+        assert_eq!(event[0], JsonValue::from(0.5));
+        assert_eq!(
+            event[2],
+            JsonValue::from(format!("{ANSI_CLEAR_SCREEN}...\r\n.#."))
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Terminal rendering of grids with overlay layers, for debugging simulations.
+//!
+//! Positions are `(row, col)` indices, the same as `grid::Grid` and [`Dir4::step`].
+//! Layers are drawn in the order they are added, so later layers win.
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use grid::Grid;
+
+use crate::direction::Dir4;
+use crate::template::{
+    flag, ANSI_BG_BLUE, ANSI_BG_CYAN, ANSI_BG_GREEN, ANSI_BG_MAGENTA, ANSI_BG_RED, ANSI_BG_YELLOW,
+    ANSI_BLUE, ANSI_BOLD, ANSI_CYAN, ANSI_DIM, ANSI_GREEN, ANSI_MAGENTA, ANSI_RED, ANSI_RESET,
+    ANSI_YELLOW,
+};
+
+/// Whether the solution was started with `cargo solve <day> --visualize`.
+pub fn visualize_requested() -> bool {
+    flag("--visualize")
+}
+
+/// Whether the solution was started with `cargo solve <day> --explain`.
+pub fn explain_requested() -> bool {
+    flag("--explain")
+}
+
+/// A terminal color, usable as foreground or background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    /// Every color, in a stable order that can be cycled through for categories.
+    pub const ALL: [Color; 6] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+
+    /// Picks a color for the `i`th category, cycling through [`Color::ALL`].
+    pub fn nth(i: usize) -> Color {
+        Self::ALL[i % Self::ALL.len()]
+    }
+
+    fn foreground(self) -> &'static str {
+        match self {
+            Color::Red => ANSI_RED,
+            Color::Green => ANSI_GREEN,
+            Color::Yellow => ANSI_YELLOW,
+            Color::Blue => ANSI_BLUE,
+            Color::Magenta => ANSI_MAGENTA,
+            Color::Cyan => ANSI_CYAN,
+        }
+    }
+
+    fn background(self) -> &'static str {
+        match self {
+            Color::Red => ANSI_BG_RED,
+            Color::Green => ANSI_BG_GREEN,
+            Color::Yellow => ANSI_BG_YELLOW,
+            Color::Blue => ANSI_BG_BLUE,
+            Color::Magenta => ANSI_BG_MAGENTA,
+            Color::Cyan => ANSI_BG_CYAN,
+        }
+    }
+}
+
+/// One rendered cell: its character and the styles the layers applied to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+}
+
+impl Cell {
+    fn plain(ch: char) -> Self {
+        Self {
+            ch,
+            fg: None,
+            bg: None,
+            bold: false,
+            dim: false,
+        }
+    }
+
+    fn is_styled(&self) -> bool {
+        self.fg.is_some() || self.bg.is_some() || self.bold || self.dim
+    }
+}
+
+/// A grid of characters with overlay layers.
+///
+/// ```
+/// # use advent_of_code::{direction::Dir4, render::{Color, GridRenderer}};
+/// let mut view = GridRenderer::from_lines("....\n.#..\n....");
+/// view.path([((2, 0), Dir4::North), ((1, 0), Dir4::North), ((0, 0), Dir4::East)], Color::Green)
+///     .mark([(1, 1)], 'O', Color::Red);
+/// assert_eq!(view.to_plain_string(), ">...\n^O..\n^...\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridRenderer {
+    cells: Grid<Cell>,
+}
+
+impl GridRenderer {
+    /// Creates a renderer of `rows` by `cols` cells, with the character of each cell given by `f`.
+    pub fn new(rows: usize, cols: usize, f: impl Fn((usize, usize)) -> char) -> Self {
+        let mut cells = Grid::init(rows, cols, Cell::plain(' '));
+        for ((row, col), cell) in cells.indexed_iter_mut() {
+            cell.ch = f((row, col));
+        }
+        Self { cells }
+    }
+
+    /// Creates a renderer from a puzzle input, one row per line. Short lines are padded with spaces.
+    pub fn from_lines(input: &str) -> Self {
+        let lines: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        let cols = lines.iter().map(Vec::len).max().unwrap_or(0);
+        Self::new(lines.len(), cols, |(row, col)| {
+            lines[row].get(col).copied().unwrap_or(' ')
+        })
+    }
+
+    /// Creates a renderer from a grid, turning each value into a character with `f`.
+    pub fn from_grid<T>(grid: &Grid<T>, f: impl Fn(&T) -> char) -> Self {
+        Self::new(grid.rows(), grid.cols(), |(row, col)| f(&grid[(row, col)]))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.cols()
+    }
+
+    /// The rendered cell at `position`, or [`None`] if it is outside the grid.
+    pub fn cell(&self, (row, col): (usize, usize)) -> Option<&Cell> {
+        self.cells.get(row, col)
+    }
+
+    fn layer(
+        &mut self,
+        positions: impl IntoIterator<Item = (usize, usize)>,
+        mut f: impl FnMut(&mut Cell),
+    ) -> &mut Self {
+        for (row, col) in positions {
+            if let Some(cell) = self.cells.get_mut(row, col) {
+                f(cell);
+            }
+        }
+        self
+    }
+
+    /// Colors the characters at `positions`.
+    pub fn color(
+        &mut self,
+        positions: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+    ) -> &mut Self {
+        self.layer(positions, |cell| cell.fg = Some(color))
+    }
+
+    /// Colors the background at `positions`.
+    pub fn highlight(
+        &mut self,
+        positions: impl IntoIterator<Item = (usize, usize)>,
+        color: Color,
+    ) -> &mut Self {
+        self.layer(positions, |cell| cell.bg = Some(color))
+    }
+
+    /// Replaces the characters at `positions` with a bold `symbol` in `color`.
+    pub fn mark(
+        &mut self,
+        positions: impl IntoIterator<Item = (usize, usize)>,
+        symbol: char,
+        color: Color,
+    ) -> &mut Self {
+        self.layer(positions, |cell| {
+            cell.ch = symbol;
+            cell.fg = Some(color);
+            cell.bold = true;
+        })
+    }
+
+    /// Draws a path as arrows pointing in the direction of travel at each position.
+    ///
+    /// Positions crossed both horizontally and vertically are drawn as `+`, positions walked
+    /// back and forth along one axis as `|` or `-`.
+    pub fn path(
+        &mut self,
+        steps: impl IntoIterator<Item = ((usize, usize), Dir4)>,
+        color: Color,
+    ) -> &mut Self {
+        let mut seen: HashMap<(usize, usize), [bool; 4]> = HashMap::new();
+        for (position, direction) in steps {
+            let directions = seen.entry(position).or_default();
+            directions[direction as usize] = true;
+            let vertical = directions[Dir4::North as usize] || directions[Dir4::South as usize];
+            let horizontal = directions[Dir4::East as usize] || directions[Dir4::West as usize];
+            let symbol = match directions.iter().filter(|&&d| d).count() {
+                1 => direction.arrow(),
+                _ if vertical && horizontal => '+',
+                _ if vertical => '|',
+                _ => '-',
+            };
+            self.layer([position], |cell| {
+                cell.ch = symbol;
+                cell.fg = Some(color);
+            });
+        }
+        self
+    }
+
+    /// Dims every cell no layer has styled yet, so the overlays stand out.
+    pub fn dim_unstyled(&mut self) -> &mut Self {
+        for cell in self.cells.iter_mut() {
+            if !cell.is_styled() {
+                cell.dim = true;
+            }
+        }
+        self
+    }
+
+    /// Renders the characters only, without any ANSI escape codes.
+    pub fn to_plain_string(&self) -> String {
+        let mut out = String::with_capacity((self.cols() + 1) * self.rows());
+        for row in self.cells.iter_rows() {
+            out.extend(row.map(|cell| cell.ch));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Display for GridRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.iter_rows() {
+            for cell in row {
+                if !cell.is_styled() {
+                    write!(f, "{}", cell.ch)?;
+                    continue;
+                }
+                if cell.bold {
+                    f.write_str(ANSI_BOLD)?;
+                }
+                if cell.dim {
+                    f.write_str(ANSI_DIM)?;
+                }
+                if let Some(fg) = cell.fg {
+                    f.write_str(fg.foreground())?;
+                }
+                if let Some(bg) = cell.bg {
+                    f.write_str(bg.background())?;
+                }
+                write!(f, "{}{ANSI_RESET}", cell.ch)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_layers_in_order() {
+        let mut view = GridRenderer::from_lines("...\n...");
+        view.path(
+            [
+                ((1, 0), Dir4::East),
+                ((1, 1), Dir4::North),
+                ((0, 1), Dir4::South),
+                ((1, 1), Dir4::East),
+            ],
+            Color::Green,
+        )
+        .mark([(0, 2), (5, 5)], '#', Color::Red);
+        assert_eq!(view.to_plain_string(), ".v#\n>+.\n");
+        assert_eq!(view.cell((0, 2)).unwrap().fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn renders_ansi_only_for_styled_cells() {
+        let mut view = GridRenderer::from_lines("ab");
+        view.highlight([(0, 1)], Color::Blue);
+        assert_eq!(view.to_string(), format!("a{ANSI_BG_BLUE}b{ANSI_RESET}\n"));
+    }
+}
@@ -0,0 +1,30 @@
+use std::{fs, path::Path, process};
+
+use crate::generate::generator;
+use crate::template::Day;
+
+const GENERATED_DIR: &str = "data/generated";
+
+pub fn handle(day: Day, size: Option<usize>, seed: u64) {
+    let Some(generator) = generator(day) else {
+        eprintln!("No input generator for day {day}.");
+        process::exit(1);
+    };
+    let size = size.unwrap_or(generator.default_size);
+    let input = generator.generate(size, seed);
+
+    let path = Path::new(GENERATED_DIR).join(format!("{day}-{size}-{seed}.txt"));
+    if let Err(e) = fs::create_dir_all(GENERATED_DIR).and_then(|()| fs::write(&path, input)) {
+        eprintln!("Failed to write generated input: {e}");
+        process::exit(1);
+    }
+    println!(
+        "Generated input with {size} {} to \"{}\"",
+        generator.unit,
+        path.display()
+    );
+    println!(
+        "🎄 Type `cargo solve {day} -- --input {}` to run your solution on it.",
+        path.display()
+    );
+}
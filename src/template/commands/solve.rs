@@ -2,7 +2,52 @@ use std::process::{Command, Stdio};
 
 use crate::template::Day;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// Flags that are forwarded to the solution binary to turn on its visualizations.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VisualizeFlags {
+    /// Print intermediate state, see `advent_of_code::render`.
+    pub visualize: bool,
+    /// Write recorded frames to a `.cast` file, see `advent_of_code::recorder`.
+    pub record: bool,
+    /// Replay recorded frames in the terminal at this many frames per second.
+    pub replay: Option<f64>,
+    /// Export images to `data/visualizations/`, see `advent_of_code::image`.
+    pub render: bool,
+    /// Print how the answer was reached, step by step.
+    pub explain: bool,
+}
+
+impl VisualizeFlags {
+    fn to_args(self) -> Vec<String> {
+        let mut args = vec![];
+        if self.visualize {
+            args.push("--visualize".to_string());
+        }
+        if self.record {
+            args.push("--record".to_string());
+        }
+        if let Some(fps) = self.replay {
+            args.push("--replay".to_string());
+            args.push(fps.to_string());
+        }
+        if self.render {
+            args.push("--render".to_string());
+        }
+        if self.explain {
+            args.push("--explain".to_string());
+        }
+        args
+    }
+}
+
+pub fn handle(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    submit_part: Option<u8>,
+    visualize: VisualizeFlags,
+    options: &[String],
+) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
     if dhat {
@@ -23,6 +68,9 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
         cmd_args.push(submit_part.to_string());
     }
 
+    cmd_args.extend(visualize.to_args());
+    cmd_args.extend(options.iter().cloned());
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
@@ -1,4 +1,4 @@
-use std::{env, fs};
+use std::{env, fs, str::FromStr};
 
 pub mod aoc_cli;
 pub mod commands;
@@ -14,6 +14,19 @@ mod timings;
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
+pub const ANSI_DIM: &str = "\x1b[2m";
+pub const ANSI_RED: &str = "\x1b[31m";
+pub const ANSI_GREEN: &str = "\x1b[32m";
+pub const ANSI_YELLOW: &str = "\x1b[33m";
+pub const ANSI_BLUE: &str = "\x1b[34m";
+pub const ANSI_MAGENTA: &str = "\x1b[35m";
+pub const ANSI_CYAN: &str = "\x1b[36m";
+pub const ANSI_BG_RED: &str = "\x1b[41m";
+pub const ANSI_BG_GREEN: &str = "\x1b[42m";
+pub const ANSI_BG_YELLOW: &str = "\x1b[43m";
+pub const ANSI_BG_BLUE: &str = "\x1b[44m";
+pub const ANSI_BG_MAGENTA: &str = "\x1b[45m";
+pub const ANSI_BG_CYAN: &str = "\x1b[46m";
 
 /// Helper function that reads a text file to a string.
 #[must_use]
@@ -24,6 +37,16 @@ pub fn read_file(folder: &str, day: Day) -> String {
     f.expect("could not open input file")
 }
 
+/// The puzzle input for `day`, or the file passed with `--input <path>`, like a generated input.
+#[must_use]
+pub fn read_input(day: Day) -> String {
+    match option::<String>("--input") {
+        Some(path) => fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("could not open input file \"{path}\": {e}")),
+        None => read_file("inputs", day),
+    }
+}
+
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
@@ -36,6 +59,20 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
     f.expect("could not open input file")
 }
 
+/// The value after `name` on the solution's command line, e.g. `500` for `--blinks 500`.
+///
+/// Options a solution reads this way are passed after `--`: `cargo solve 11 -- --blinks 500`.
+pub fn option<T: FromStr>(name: &str) -> Option<T> {
+    let args: Vec<String> = env::args().collect();
+    let index = args.iter().position(|x| x == name)?;
+    args.get(index + 1)?.parse().ok()
+}
+
+/// Whether `name` was passed on the solution's command line, e.g. `--reduce`.
+pub fn flag(name: &str) -> bool {
+    env::args().any(|x| x == name)
+}
+
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
@@ -61,7 +98,7 @@ macro_rules! solution {
 
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
+            let input = $crate::template::read_input(DAY);
             $( run_part($func, &input, DAY, $part); )*
         }
     };
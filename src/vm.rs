@@ -0,0 +1,377 @@
+//! A tiny VM for "corrupted program" puzzles: instructions like `mul(2,4)` hidden in noise.
+//!
+//! An [`InstructionSet`] says which `name(args)` patterns to pick out of the text and what each
+//! one does to the machine's named registers and flags. [`Vm::run`] executes them in order and
+//! returns a [`Trace`] recording which instructions ran and which were skipped, and why.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
+
+use nom::{
+    character::complete::{char, digit1},
+    multi::separated_list0,
+    IResult, Parser,
+};
+
+use crate::parse::call;
+
+/// What an instruction does when it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// Adds the product of the arguments to a register.
+    AddProduct(String),
+    /// Adds the sum of the arguments to a register.
+    AddSum(String),
+    /// Sets a register to the first argument.
+    Set(String),
+    /// Turns a flag on or off.
+    Flag(String, bool),
+}
+
+/// An instruction the tokenizer looks for, written `name(arg,arg,...)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    name: String,
+    arity: usize,
+    effect: Effect,
+    guard: Option<String>,
+}
+
+impl Pattern {
+    /// Matches `name` followed by exactly `arity` comma-separated unsigned integers in
+    /// parentheses.
+    pub fn new(name: &str, arity: usize, effect: Effect) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            effect,
+            guard: None,
+        }
+    }
+
+    /// Only runs the instruction while `flag` is on. Guard flags start out on.
+    pub fn guard(mut self, flag: &str) -> Self {
+        self.guard = Some(flag.to_string());
+        self
+    }
+}
+
+/// The patterns to pick out of a noisy program.
+///
+/// ```
+/// # use advent_of_code::vm::{Effect, InstructionSet, Pattern, Vm};
+/// let set = InstructionSet::new()
+///     .max_digits(3)
+///     .instruction(Pattern::new("mul", 2, Effect::AddProduct("sum".into())).guard("on"))
+///     .instruction(Pattern::new("don't", 0, Effect::Flag("on".into(), false)));
+/// let mut vm = Vm::new(&set);
+/// let trace = vm.run("mul(2,3)%mul(4,5]mul(1000,1)don't()_mul(7,7)");
+/// assert_eq!(vm.register("sum"), 6);
+/// assert_eq!(trace.ran().count(), 2);
+/// assert_eq!(trace.skipped().count(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    patterns: Vec<Pattern>,
+    max_digits: Option<usize>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pattern. Patterns are tried in the order they were added.
+    pub fn instruction(mut self, pattern: Pattern) -> Self {
+        self.patterns.push(pattern);
+        self
+    }
+
+    /// Rejects arguments with more than `n` digits, like `mul(1000,1)`.
+    pub fn max_digits(mut self, n: usize) -> Self {
+        self.max_digits = Some(n);
+        self
+    }
+
+    fn args(input: &str) -> IResult<&str, Vec<&str>> {
+        separated_list0(char(','), digit1)(input)
+    }
+
+    /// The pattern and arguments of an instruction at the very start of `input`, and its length.
+    fn match_at<'a>(&'a self, input: &str) -> Option<(&'a Pattern, Vec<i64>, usize)> {
+        self.patterns.iter().find_map(|pattern| {
+            let (rest, args) = call(&pattern.name, Self::args).parse(input).ok()?;
+            if args.len() != pattern.arity {
+                return None;
+            }
+            let too_long = |arg: &&str| self.max_digits.is_some_and(|n| arg.len() > n);
+            if args.iter().any(too_long) {
+                return None;
+            }
+            let args = args
+                .iter()
+                .map(|arg| arg.parse().ok())
+                .collect::<Option<_>>()?;
+            Some((pattern, args, input.len() - rest.len()))
+        })
+    }
+
+    /// Picks every instruction out of `text`, skipping everything in between.
+    pub fn tokenize<'a>(&'a self, text: &str) -> Vec<Instruction<'a>> {
+        let mut instructions = vec![];
+        let mut offset = 0;
+        while offset < text.len() {
+            match self.match_at(&text[offset..]) {
+                Some((pattern, args, len)) => {
+                    instructions.push(Instruction {
+                        pattern,
+                        args,
+                        span: offset..offset + len,
+                    });
+                    offset += len;
+                }
+                None => offset += text[offset..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        instructions
+    }
+}
+
+/// An instruction found in a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction<'a> {
+    pub pattern: &'a Pattern,
+    pub args: Vec<i64>,
+    /// Where the instruction was found, in bytes.
+    pub span: Range<usize>,
+}
+
+impl Display for Instruction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let args: Vec<String> = self.args.iter().map(i64::to_string).collect();
+        write!(f, "{}({})", self.pattern.name, args.join(","))
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// What happened to one instruction during a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The instruction changed a register to `value`.
+    Register { name: String, value: i64 },
+    /// The instruction turned a flag on or off.
+    Flag { name: String, on: bool },
+    /// The instruction did not run because its guard `flag` was off, as set by the instruction at
+    /// `since`.
+    Skipped { flag: String, since: Option<usize> },
+    /// The instruction would have overflowed a register, which was left unchanged.
+    Overflowed { name: String },
+}
+
+/// One step of a [`Trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<'a> {
+    pub instruction: Instruction<'a>,
+    pub outcome: Outcome,
+}
+
+impl Display for Step<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instruction = self.instruction.to_string();
+        write!(f, "{:>6}  {instruction:<16} ", self.instruction.span.start)?;
+        match &self.outcome {
+            Outcome::Register { name, value } => write!(f, "ran: {name} = {value}"),
+            Outcome::Flag { name, on } => {
+                write!(f, "ran: {name} {}", if *on { "on" } else { "off" })
+            }
+            Outcome::Overflowed { name } => write!(f, "overflowed: {name} unchanged"),
+            Outcome::Skipped { flag, since } => {
+                write!(f, "skipped: {flag} is off")?;
+                match since {
+                    Some(offset) => write!(f, " since offset {offset}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Every instruction a [`Vm`] saw, in order, with what it did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace<'a> {
+    pub steps: Vec<Step<'a>>,
+}
+
+impl<'a> Trace<'a> {
+    /// The instructions that ran, including ones that overflowed.
+    pub fn ran(&self) -> impl Iterator<Item = &Step<'a>> {
+        self.steps
+            .iter()
+            .filter(|step| !matches!(step.outcome, Outcome::Skipped { .. }))
+    }
+
+    /// The instructions that were skipped because of a guard.
+    pub fn skipped(&self) -> impl Iterator<Item = &Step<'a>> {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step.outcome, Outcome::Skipped { .. }))
+    }
+
+    /// The first instruction that would have overflowed a register, if any.
+    pub fn overflow(&self) -> Option<&Step<'a>> {
+        self.steps
+            .iter()
+            .find(|step| matches!(step.outcome, Outcome::Overflowed { .. }))
+    }
+}
+
+impl Display for Trace<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A machine with named integer registers (starting at 0) and named flags (starting on).
+#[derive(Debug, Clone)]
+pub struct Vm<'a> {
+    set: &'a InstructionSet,
+    registers: HashMap<String, i64>,
+    /// Whether each flag is on, and the offset of the instruction that last set it.
+    flags: HashMap<String, (bool, usize)>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(set: &'a InstructionSet) -> Self {
+        Self {
+            set,
+            registers: HashMap::new(),
+            flags: HashMap::new(),
+        }
+    }
+
+    /// The value of a register, 0 if no instruction has touched it.
+    pub fn register(&self, name: &str) -> i64 {
+        self.registers.get(name).copied().unwrap_or(0)
+    }
+
+    /// Whether a flag is on. Flags no instruction has set are on.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.get(name).is_none_or(|&(on, _)| on)
+    }
+
+    /// Runs one instruction.
+    pub fn execute(&mut self, instruction: &Instruction) -> Outcome {
+        if let Some(guard) = &instruction.pattern.guard {
+            if !self.flag(guard) {
+                return Outcome::Skipped {
+                    flag: guard.clone(),
+                    since: self.flags.get(guard).map(|&(_, offset)| offset),
+                };
+            }
+        }
+        let args = &instruction.args;
+        let (name, value) = match &instruction.pattern.effect {
+            Effect::AddProduct(name) => (
+                name,
+                args.iter()
+                    .try_fold(1i64, |product, &arg| product.checked_mul(arg))
+                    .and_then(|product| self.register(name).checked_add(product)),
+            ),
+            Effect::AddSum(name) => (
+                name,
+                args.iter()
+                    .try_fold(self.register(name), |sum, &arg| sum.checked_add(arg)),
+            ),
+            Effect::Set(name) => (name, Some(args.first().copied().unwrap_or(0))),
+            Effect::Flag(name, on) => {
+                self.flags
+                    .insert(name.clone(), (*on, instruction.span.start));
+                return Outcome::Flag {
+                    name: name.clone(),
+                    on: *on,
+                };
+            }
+        };
+        let Some(value) = value else {
+            return Outcome::Overflowed { name: name.clone() };
+        };
+        self.registers.insert(name.clone(), value);
+        Outcome::Register {
+            name: name.clone(),
+            value,
+        }
+    }
+
+    /// Tokenizes `text` and runs every instruction in it.
+    pub fn run(&mut self, text: &str) -> Trace<'a> {
+        let steps = self
+            .set
+            .tokenize(text)
+            .into_iter()
+            .map(|instruction| Step {
+                outcome: self.execute(&instruction),
+                instruction,
+            })
+            .collect();
+        Trace { steps }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_noisy_text() {
+        let set = InstructionSet::new()
+            .instruction(Pattern::new("add", 3, Effect::AddSum("x".into())))
+            .instruction(Pattern::new("set", 1, Effect::Set("x".into())));
+        let tokens = set.tokenize("set(5)xadd(1,2,-3)add(1,2)set (4)add(1,2,3)");
+        let found: Vec<String> = tokens.iter().map(ToString::to_string).collect();
+        assert_eq!(found, ["set(5)", "add(1,2,3)"]);
+        assert_eq!(tokens[1].span, 33..43);
+    }
+
+    #[test]
+    fn explains_skipped_instructions() {
+        let set = InstructionSet::new()
+            .instruction(Pattern::new("inc", 1, Effect::AddSum("n".into())).guard("on"))
+            .instruction(Pattern::new("off", 0, Effect::Flag("on".into(), false)))
+            .instruction(Pattern::new("on", 0, Effect::Flag("on".into(), true)));
+        let mut vm = Vm::new(&set);
+        let trace = vm.run("inc(1)off()inc(2)on()inc(3)");
+        assert_eq!(vm.register("n"), 4);
+        assert_eq!(
+            trace.steps[2].outcome,
+            Outcome::Skipped {
+                flag: "on".into(),
+                since: Some(6)
+            }
+        );
+        assert_eq!(
+            trace.steps[2].to_string(),
+            "    11  inc(2)           skipped: on is off since offset 6"
+        );
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let set = InstructionSet::new().instruction(Pattern::new(
+            "mul",
+            2,
+            Effect::AddProduct("x".into()),
+        ));
+        let mut vm = Vm::new(&set);
+        let trace = vm.run("mul(3,4)mul(9999999999,9999999999)mul(1,2)");
+        assert_eq!(vm.register("x"), 14);
+        let overflow = trace.overflow().unwrap();
+        assert_eq!(overflow.instruction.span.start, 8);
+        assert!(overflow.to_string().ends_with("overflowed: x unchanged"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Word searches over character grids.
+//!
+//! [`WordSearch`] finds any number of words along the rows, columns and diagonals of a grid in
+//! all eight directions, scanning each line once with an Aho-Corasick automaton. [`Shape`]
+//! matches 2D masks (like an X of `MAS`) in every requested rotation and reflection.
+//!
+//! Positions are `(row, col)` indices, the same as `grid::Grid` and [`Dir8::step`].
+use std::collections::{HashMap, VecDeque};
+
+use grid::Grid;
+
+use crate::direction::Dir8;
+
+/// An Aho-Corasick automaton over characters.
+#[derive(Debug, Clone)]
+struct Automaton {
+    /// Trie edges of every node. Node 0 is the root.
+    edges: Vec<HashMap<char, usize>>,
+    /// The node for the longest proper suffix of each node that is also in the trie.
+    fail: Vec<usize>,
+    /// Indices of the words ending at each node, including those reached through `fail`.
+    outputs: Vec<Vec<usize>>,
+}
+
+impl Automaton {
+    fn new<S: AsRef<str>>(words: &[S]) -> Self {
+        let mut automaton = Self {
+            edges: vec![HashMap::new()],
+            fail: vec![0],
+            outputs: vec![vec![]],
+        };
+        for (index, word) in words.iter().enumerate() {
+            let mut node = 0;
+            for c in word.as_ref().chars() {
+                node = match automaton.edges[node].get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        automaton.edges.push(HashMap::new());
+                        automaton.fail.push(0);
+                        automaton.outputs.push(vec![]);
+                        let next = automaton.edges.len() - 1;
+                        automaton.edges[node].insert(c, next);
+                        next
+                    }
+                };
+            }
+            if node != 0 {
+                automaton.outputs[node].push(index);
+            }
+        }
+
+        // Breadth-first, so the fail link of every shallower node is known before it is needed.
+        let mut queue: VecDeque<usize> = automaton.edges[0].values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(char, usize)> = automaton.edges[node]
+                .iter()
+                .map(|(&c, &n)| (c, n))
+                .collect();
+            for (c, child) in children {
+                let fail = automaton.next(automaton.fail[node], c);
+                automaton.fail[child] = fail;
+                let inherited = automaton.outputs[fail].clone();
+                automaton.outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        automaton
+    }
+
+    /// The node reached from `node` by reading `c`.
+    fn next(&self, mut node: usize, c: char) -> usize {
+        loop {
+            if let Some(&next) = self.edges[node].get(&c) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.fail[node];
+        }
+    }
+}
+
+/// A word found by [`WordSearch::find`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WordMatch {
+    /// The index of the word in the list the search was created with.
+    pub word: usize,
+    /// The position of the first character of the word.
+    pub start: (usize, usize),
+    /// The direction the word reads in.
+    pub direction: Dir8,
+}
+
+/// Finds a list of words in all eight directions of a grid.
+///
+/// ```
+/// # use advent_of_code::{direction::Dir8, parse::char_grid, wordsearch::WordSearch};
+/// let grid = char_grid("CAT\nXOX\nGOD");
+/// let matches = WordSearch::new(&["CAT", "DOG", "TOG"]).find(&grid);
+/// assert_eq!(matches.len(), 3);
+/// assert!(matches.iter().any(|m| m.word == 1 && m.start == (2, 2) && m.direction == Dir8::West));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    automaton: Automaton,
+    lengths: Vec<usize>,
+}
+
+impl WordSearch {
+    pub fn new<S: AsRef<str>>(words: &[S]) -> Self {
+        Self {
+            automaton: Automaton::new(words),
+            lengths: words.iter().map(|w| w.as_ref().chars().count()).collect(),
+        }
+    }
+
+    /// Every occurrence of every word, in any direction. Palindromes are found in both directions.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<WordMatch> {
+        let (rows, cols) = (grid.rows(), grid.cols());
+        let mut matches = vec![];
+        for direction in Dir8::iter() {
+            let line_starts = grid
+                .indexed_iter()
+                .map(|(position, _)| position)
+                .filter(|&position| direction.opposite().step(position, rows, cols).is_none());
+            for line_start in line_starts {
+                let line: Vec<(usize, usize)> =
+                    std::iter::successors(Some(line_start), |&p| direction.step(p, rows, cols))
+                        .collect();
+                let mut node = 0;
+                for (i, &position) in line.iter().enumerate() {
+                    node = self.automaton.next(node, grid[position]);
+                    for &word in &self.automaton.outputs[node] {
+                        matches.push(WordMatch {
+                            word,
+                            start: line[i + 1 - self.lengths[word]],
+                            direction,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Which orientations of a [`Shape`] to look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    /// Only the shape as written.
+    #[default]
+    None,
+    /// The shape turned by any number of quarter turns.
+    Rotations,
+    /// The rotations of the shape and of its mirror image.
+    RotationsAndReflections,
+}
+
+/// How a [`Shape`] was turned to produce a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Orientation {
+    /// Quarter turns clockwise, applied after the reflection.
+    pub quarter_turns: u8,
+    /// Whether the shape was mirrored left to right.
+    pub reflected: bool,
+}
+
+/// A match found by [`Shape::find`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShapeMatch {
+    /// The position of the top left corner of the oriented shape's bounding box.
+    pub top_left: (usize, usize),
+    pub orientation: Orientation,
+}
+
+/// A 2D mask of characters, where `.` matches anything.
+///
+/// ```
+/// # use advent_of_code::{parse::char_grid, wordsearch::{Shape, Symmetry}};
+/// let x_mas = Shape::parse("M.S\n.A.\nM.S");
+/// let grid = char_grid("S.S\n.A.\nM.M");
+/// let matches = x_mas.find(&grid, Symmetry::Rotations);
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].orientation.quarter_turns, 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    /// The `(row, col)` offset and character of every non-wildcard cell, sorted by offset.
+    cells: Vec<((usize, usize), char)>,
+}
+
+impl Shape {
+    /// Reads a shape with one row per line, using `.` for cells that match anything.
+    pub fn parse(pattern: &str) -> Self {
+        let cells = pattern
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(col, c)| ((row, col), c))
+            })
+            .filter(|&(_, c)| c != '.');
+        Self::normalized(cells.map(|((row, col), c)| ((row as isize, col as isize), c)))
+    }
+
+    /// Moves the cells so the bounding box starts at `(0, 0)`.
+    fn normalized(cells: impl Iterator<Item = ((isize, isize), char)>) -> Self {
+        let cells: Vec<_> = cells.collect();
+        let min_row = cells.iter().map(|&((r, _), _)| r).min().unwrap_or(0);
+        let min_col = cells.iter().map(|&((_, c), _)| c).min().unwrap_or(0);
+        let mut cells: Vec<_> = cells
+            .into_iter()
+            .map(|((r, c), ch)| (((r - min_row) as usize, (c - min_col) as usize), ch))
+            .collect();
+        cells.sort_unstable();
+        Self { cells }
+    }
+
+    /// The shape turned into `orientation`.
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        Self::normalized(self.cells.iter().map(|&((row, col), ch)| {
+            let (mut r, mut c) = (row as isize, col as isize);
+            if orientation.reflected {
+                c = -c;
+            }
+            for _ in 0..orientation.quarter_turns % 4 {
+                (r, c) = (c, -r);
+            }
+            ((r, c), ch)
+        }))
+    }
+
+    /// The distinct orientations allowed by `symmetry`. Orientations that look the same as an
+    /// earlier one are skipped, so a symmetric shape is not matched twice at the same place.
+    pub fn orientations(&self, symmetry: Symmetry) -> Vec<(Orientation, Shape)> {
+        let reflections: &[bool] = match symmetry {
+            Symmetry::RotationsAndReflections => &[false, true],
+            _ => &[false],
+        };
+        let turns = if symmetry == Symmetry::None { 1 } else { 4 };
+        let mut result: Vec<(Orientation, Shape)> = vec![];
+        for &reflected in reflections {
+            for quarter_turns in 0..turns {
+                let orientation = Orientation {
+                    quarter_turns,
+                    reflected,
+                };
+                let shape = self.oriented(orientation);
+                if result.iter().all(|(_, seen)| *seen != shape) {
+                    result.push((orientation, shape));
+                }
+            }
+        }
+        result
+    }
+
+    fn matches_at(&self, grid: &Grid<char>, (row, col): (usize, usize)) -> bool {
+        self.cells
+            .iter()
+            .all(|&((r, c), ch)| grid.get(row + r, col + c) == Some(&ch))
+    }
+
+    /// Every place and orientation the shape matches, ordered by orientation, then position.
+    pub fn find(&self, grid: &Grid<char>, symmetry: Symmetry) -> Vec<ShapeMatch> {
+        self.orientations(symmetry)
+            .into_iter()
+            .flat_map(|(orientation, shape)| {
+                grid.indexed_iter()
+                    .map(|(position, _)| position)
+                    .filter(move |&position| shape.matches_at(grid, position))
+                    .map(move |top_left| ShapeMatch {
+                        top_left,
+                        orientation,
+                    })
+            })
+            .collect()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::char_grid;
+
+    #[test]
+    fn finds_overlapping_words() {
+        let grid = char_grid("ABAB\nXXXX");
+        let matches = WordSearch::new(&["ABA", "BA", "A"]).find(&grid);
+        let forwards: Vec<_> = matches
+            .iter()
+            .filter(|m| m.direction == Dir8::East)
+            .map(|m| (m.word, m.start))
+            .collect();
+        assert_eq!(
+            forwards,
+            [(2, (0, 0)), (0, (0, 0)), (1, (0, 1)), (2, (0, 2))]
+        );
+        // "A" is found once per direction at each of its two positions.
+        assert_eq!(matches.iter().filter(|m| m.word == 2).count(), 16);
+    }
+
+    #[test]
+    fn skips_duplicate_orientations() {
+        let plus = Shape::parse(".A.\nAAA\n.A.");
+        assert_eq!(
+            plus.orientations(Symmetry::RotationsAndReflections).len(),
+            1
+        );
+        let ell = Shape::parse("A.\nAB");
+        assert_eq!(ell.orientations(Symmetry::Rotations).len(), 4);
+        assert_eq!(ell.orientations(Symmetry::RotationsAndReflections).len(), 8);
+        assert_eq!(
+            ell.oriented(Orientation {
+                quarter_turns: 1,
+                reflected: false
+            }),
+            Shape::parse("AA\nB.")
+        );
+    }
+}